@@ -0,0 +1,81 @@
+use crate::rpc::{LatestBlockhash, RpcClient, RpcError};
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// Blockhashes live for roughly 60-90s; refetch well before that.
+pub const DEFAULT_BLOCKHASH_TTL: Duration = Duration::from_secs(30);
+
+/// The most recently fetched blockhash, shared by concurrent mints so a
+/// burst of requests costs one `getLatestBlockhash` instead of one each.
+pub struct BlockhashCache {
+    ttl: Duration,
+    latest: RwLock<Option<LatestBlockhash>>,
+}
+
+impl Default for BlockhashCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_BLOCKHASH_TTL)
+    }
+}
+
+impl BlockhashCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            latest: RwLock::new(None),
+        }
+    }
+
+    /// The cached blockhash, fetched first if missing or older than the TTL.
+    pub async fn get(&self, rpc: &RpcClient) -> Result<LatestBlockhash, RpcError> {
+        if let Some(latest) = *self.latest.read().await {
+            if latest.age() < self.ttl {
+                return Ok(latest);
+            }
+        }
+
+        let mut latest = self.latest.write().await;
+        // Another request may have refreshed it while we waited.
+        if let Some(cached) = *latest {
+            if cached.age() < self.ttl {
+                return Ok(cached);
+            }
+        }
+        let fresh = rpc.get_latest_blockhash_with_context().await?;
+        *latest = Some(fresh);
+        Ok(fresh)
+    }
+
+    /// Fetch a new blockhash regardless of age, e.g. after the cached one
+    /// was rejected as expired.
+    pub async fn refresh(&self, rpc: &RpcClient) -> Result<LatestBlockhash, RpcError> {
+        let mut latest = self.latest.write().await;
+        let fresh = rpc.get_latest_blockhash_with_context().await?;
+        *latest = Some(fresh);
+        Ok(fresh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_rpc::MockRpc;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_refetches_only_after_ttl() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        let cache = BlockhashCache::new(Duration::from_secs(30));
+
+        cache.get(&rpc).await.unwrap();
+        cache.get(&rpc).await.unwrap();
+        assert_eq!(mock.calls("getLatestBlockhash"), 1);
+
+        tokio::time::advance(Duration::from_secs(31)).await;
+        cache.get(&rpc).await.unwrap();
+        assert_eq!(mock.calls("getLatestBlockhash"), 2);
+
+        cache.refresh(&rpc).await.unwrap();
+        assert_eq!(mock.calls("getLatestBlockhash"), 3);
+    }
+}
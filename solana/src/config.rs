@@ -0,0 +1,48 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Read `key` from the environment, falling back to `default` when it is
+/// unset or fails to parse.
+pub fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Read `key` from the environment, falling back to `default` when it is
+/// unset or empty. Unlike `env_or`, a value that fails to parse is an error:
+/// for settings like `HASH_ALGO` a typo must not silently pick the default.
+pub fn env_parse<T: FromStr>(key: &str, default: T) -> Result<T, String>
+where
+    T::Err: Display,
+{
+    match std::env::var(key) {
+        Ok(value) if !value.is_empty() => value
+            .parse()
+            .map_err(|e| format!("Invalid {}={:?}: {}", key, value, e)),
+        _ => Ok(default),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mint::HashAlgo;
+
+    #[test]
+    fn test_env_parse_rejects_invalid_value() {
+        std::env::set_var("ENV_PARSE_TEST_ALGO", "blake");
+        let err = env_parse("ENV_PARSE_TEST_ALGO", HashAlgo::Sha256).unwrap_err();
+        assert!(err.contains("ENV_PARSE_TEST_ALGO"), "{}", err);
+        std::env::set_var("ENV_PARSE_TEST_ALGO", "blake3");
+        assert_eq!(
+            env_parse("ENV_PARSE_TEST_ALGO", HashAlgo::Sha256),
+            Ok(HashAlgo::Blake3)
+        );
+        assert_eq!(
+            env_parse("ENV_PARSE_TEST_UNSET", HashAlgo::Sha256),
+            Ok(HashAlgo::Sha256)
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use crate::mint::{FailureCode, MintError, MintRequest};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// A failed mint, as written to the dead-letter log. Only the record fields
+/// of `request` are kept; delivery options such as `sla` and `confirm` are
+/// back at their defaults on replay.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeadLetterEntry {
+    pub request: MintRequest,
+    pub code: FailureCode,
+    pub error: String,
+    pub failed_at: String,
+    /// Transaction of a timed-out mint, which may have landed after all.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_signature: Option<String>,
+    /// Set once the entry is resolved: the signature of its successful
+    /// replay, or of the original transaction if that landed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replayed_signature: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplayRequest {
+    /// Zero-based line of the entry in the dead-letter log.
+    pub index: usize,
+}
+
+/// JSONL file of failed mints, one entry per line, so operators can
+/// inspect and replay them. Entries are only appended, and updated in place
+/// when replayed, so line numbers stay stable. Clones share the file's
+/// locks. The methods do blocking I/O.
+#[derive(Clone)]
+pub struct DeadLetterLog {
+    path: PathBuf,
+    write_lock: Arc<Mutex<()>>,
+    /// Held across a whole replay so the same entry is not minted twice.
+    replay_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl DeadLetterLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            write_lock: Arc::default(),
+            replay_lock: Arc::default(),
+        }
+    }
+
+    /// Wait for any other replay to finish.
+    pub async fn lock_replays(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.replay_lock.lock().await
+    }
+
+    pub fn record(&self, request: MintRequest, error: &MintError) -> Result<(), String> {
+        let entry = DeadLetterEntry {
+            request,
+            code: error.code,
+            error: error.message.clone(),
+            failed_at: Utc::now().to_rfc3339(),
+            pending_signature: error.pending_signature.clone(),
+            replayed_signature: None,
+        };
+        let line = serde_json::to_string(&entry)
+            .map_err(|e| format!("Failed to serialize dead letter: {}", e))?;
+
+        let _guard = self.write_lock.lock().unwrap();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open {}: {}", self.path.display(), e))?;
+        writeln!(file, "{}", line)
+            .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))
+    }
+
+    /// The entry on line `index`, or `None` past the end of the log.
+    pub fn get(&self, index: usize) -> Result<Option<DeadLetterEntry>, String> {
+        let data = match fs::read_to_string(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Failed to read {}: {}", self.path.display(), e)),
+        };
+        data.lines()
+            .nth(index)
+            .map(|line| {
+                serde_json::from_str(line)
+                    .map_err(|e| format!("Corrupt dead letter at line {}: {}", index, e))
+            })
+            .transpose()
+    }
+
+    /// Record that the entry on line `index` was resolved by `signature`,
+    /// rewriting the file through a temporary copy.
+    pub fn mark_replayed(&self, index: usize, signature: &str) -> Result<(), String> {
+        let _guard = self.write_lock.lock().unwrap();
+        let data = fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read {}: {}", self.path.display(), e))?;
+        let mut lines: Vec<String> = data.lines().map(String::from).collect();
+        let line = lines
+            .get_mut(index)
+            .ok_or_else(|| format!("No dead letter at index {}", index))?;
+        let mut entry: DeadLetterEntry = serde_json::from_str(line)
+            .map_err(|e| format!("Corrupt dead letter at line {}: {}", index, e))?;
+        entry.replayed_signature = Some(signature.to_string());
+        *line = serde_json::to_string(&entry)
+            .map_err(|e| format!("Failed to serialize dead letter: {}", e))?;
+
+        let tmp = self.path.with_extension("jsonl.tmp");
+        fs::write(&tmp, lines.join("\n") + "\n")
+            .map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+        fs::rename(&tmp, &self.path)
+            .map_err(|e| format!("Failed to replace {}: {}", self.path.display(), e))
+    }
+}
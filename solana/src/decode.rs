@@ -0,0 +1,129 @@
+use crate::mint::{decode_memo, parse_chunk, MemoRecord, MintConfig, MEMO_PROGRAM_ID};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use solana_transaction::Transaction;
+
+#[derive(Debug, Deserialize)]
+pub struct DecodeRequest {
+    /// Base64 of a bincode-serialized transaction.
+    pub transaction: String,
+    /// Also check that every required signature is valid for the message.
+    #[serde(default)]
+    pub verify_signatures: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DecodeResponse {
+    pub signatures: Vec<String>,
+    /// Every memo instruction's data, in order.
+    pub memos: Vec<String>,
+    /// Memos that parse as complete records. Chunks of a split record are
+    /// only listed under `memos`.
+    pub records: Vec<MemoRecord>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature_valid: Option<bool>,
+}
+
+/// Whether `tx` carries every signature its message requires and each one
+/// is valid for the message.
+fn signatures_valid(tx: &Transaction) -> bool {
+    tx.signatures.len() == tx.message.header.num_required_signatures as usize && tx.verify().is_ok()
+}
+
+pub fn decode_transaction(
+    req: &DecodeRequest,
+    config: &MintConfig,
+) -> Result<DecodeResponse, String> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(&req.transaction)
+        .map_err(|e| format!("Invalid base64 transaction: {}", e))?;
+    let tx: Transaction =
+        bincode::deserialize(&bytes).map_err(|e| format!("Invalid transaction: {}", e))?;
+
+    let memos: Vec<String> = tx
+        .message
+        .instructions
+        .iter()
+        .filter(|ix| {
+            tx.message
+                .account_keys
+                .get(ix.program_id_index as usize)
+                .is_some_and(|program| program.to_string() == MEMO_PROGRAM_ID)
+        })
+        .map(|ix| String::from_utf8_lossy(&ix.data).into_owned())
+        .collect();
+    let records = memos
+        .iter()
+        .filter(|memo| parse_chunk(memo).is_none())
+        .filter_map(|memo| decode_memo(memo, config).ok())
+        .collect();
+
+    Ok(DecodeResponse {
+        signatures: tx.signatures.iter().map(|s| s.to_string()).collect(),
+        memos,
+        records,
+        signature_valid: req.verify_signatures.then(|| signatures_valid(&tx)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mint::{build_memo, encode_memo, MintRequest};
+    use solana_hash::Hash;
+    use solana_instruction::{AccountMeta, Instruction};
+    use solana_keypair::Keypair;
+    use solana_pubkey::Pubkey;
+    use solana_signer::Signer;
+    use std::str::FromStr;
+
+    fn signed_memo_tx() -> Transaction {
+        let payer = Keypair::new();
+        let config = MintConfig::default();
+        let req = MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            ..Default::default()
+        };
+        let memo = encode_memo(&build_memo(&req, &config), &config).unwrap();
+        let instruction = Instruction {
+            program_id: Pubkey::from_str(MEMO_PROGRAM_ID).unwrap(),
+            accounts: vec![AccountMeta::new_readonly(payer.pubkey(), true)],
+            data: memo.into_bytes(),
+        };
+        Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer],
+            Hash::default(),
+        )
+    }
+
+    fn request(tx: &Transaction) -> DecodeRequest {
+        DecodeRequest {
+            transaction: base64::engine::general_purpose::STANDARD
+                .encode(bincode::serialize(tx).unwrap()),
+            verify_signatures: true,
+        }
+    }
+
+    #[test]
+    fn test_decode_valid_transaction() {
+        let tx = signed_memo_tx();
+        let decoded = decode_transaction(&request(&tx), &MintConfig::default()).unwrap();
+        assert_eq!(decoded.signature_valid, Some(true));
+        assert_eq!(decoded.records.len(), 1);
+        assert_eq!(decoded.records[0].location_id, "iceland-reykjavik");
+    }
+
+    #[test]
+    fn test_decode_flags_tampered_transaction() {
+        let mut tx = signed_memo_tx();
+        let data = &mut tx.message.instructions[0].data;
+        let text = String::from_utf8(data.clone()).unwrap();
+        *data = text.replace("reykjavik", "akureyri_").into_bytes();
+
+        let decoded = decode_transaction(&request(&tx), &MintConfig::default()).unwrap();
+        assert_eq!(decoded.signature_valid, Some(false));
+        assert_eq!(decoded.records[0].location_id, "iceland-akureyri_");
+    }
+}
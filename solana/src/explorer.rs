@@ -0,0 +1,135 @@
+use reqwest::Url;
+
+const EXPLORER_BASE: &str = "https://explorer.solana.com";
+
+const MAINNET_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+const TESTNET_GENESIS_HASH: &str = "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY";
+const DEVNET_GENESIS_HASH: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+
+/// Cluster a Solana Explorer link points at.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Cluster {
+    Mainnet,
+    Testnet,
+    #[default]
+    Devnet,
+    /// Any other RPC endpoint (e.g. a local validator), passed to the
+    /// explorer as `customUrl`.
+    Custom(String),
+}
+
+impl std::str::FromStr for Cluster {
+    type Err = String;
+
+    /// A cluster name (`mainnet`/`mainnet-beta`, `testnet`, `devnet`) or an
+    /// RPC URL.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mainnet" | "mainnet-beta" => Ok(Cluster::Mainnet),
+            "testnet" => Ok(Cluster::Testnet),
+            "devnet" => Ok(Cluster::Devnet),
+            url if Url::parse(url).is_ok() => Ok(Cluster::from_rpc_url(url)),
+            other => Err(format!("Unknown cluster: {}", other)),
+        }
+    }
+}
+
+impl Cluster {
+    /// Guess the cluster from an RPC URL's host, e.g.
+    /// `api.mainnet-beta.solana.com` or `devnet.helius-rpc.com`.
+    pub fn from_rpc_url(rpc_url: &str) -> Self {
+        let host = Url::parse(rpc_url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+            .unwrap_or_default();
+        if host.contains("mainnet") {
+            Cluster::Mainnet
+        } else if host.contains("testnet") {
+            Cluster::Testnet
+        } else if host.contains("devnet") {
+            Cluster::Devnet
+        } else {
+            Cluster::Custom(rpc_url.to_string())
+        }
+    }
+
+    /// The public cluster with genesis hash `genesis_hash`, or `Custom` for
+    /// any other chain. Unlike `from_rpc_url`, this sees through custom
+    /// domains and proxies.
+    pub fn from_genesis_hash(genesis_hash: &str, rpc_url: &str) -> Self {
+        match genesis_hash {
+            MAINNET_GENESIS_HASH => Cluster::Mainnet,
+            TESTNET_GENESIS_HASH => Cluster::Testnet,
+            DEVNET_GENESIS_HASH => Cluster::Devnet,
+            _ => Cluster::Custom(rpc_url.to_string()),
+        }
+    }
+
+    /// Explorer link for the transaction `signature` on this cluster.
+    pub fn tx_url(&self, signature: &str) -> String {
+        let mut url = Url::parse(&format!("{}/tx/{}", EXPLORER_BASE, signature))
+            .expect("Explorer base URL is valid");
+        match self {
+            Cluster::Mainnet => {}
+            Cluster::Testnet => {
+                url.query_pairs_mut().append_pair("cluster", "testnet");
+            }
+            Cluster::Devnet => {
+                url.query_pairs_mut().append_pair("cluster", "devnet");
+            }
+            Cluster::Custom(rpc_url) => {
+                url.query_pairs_mut()
+                    .append_pair("cluster", "custom")
+                    .append_pair("customUrl", rpc_url);
+            }
+        }
+        url.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_rpc_urls_map_to_explorer_urls() {
+        let cases = [
+            (
+                "https://api.mainnet-beta.solana.com",
+                "https://explorer.solana.com/tx/sig",
+            ),
+            (
+                "https://api.testnet.solana.com",
+                "https://explorer.solana.com/tx/sig?cluster=testnet",
+            ),
+            (
+                "https://api.devnet.solana.com",
+                "https://explorer.solana.com/tx/sig?cluster=devnet",
+            ),
+            (
+                "http://127.0.0.1:8899",
+                "https://explorer.solana.com/tx/sig?cluster=custom&customUrl=http%3A%2F%2F127.0.0.1%3A8899",
+            ),
+        ];
+        for (rpc_url, expected) in cases {
+            assert_eq!(Cluster::from_rpc_url(rpc_url).tx_url("sig"), expected);
+        }
+    }
+
+    #[test]
+    fn test_known_genesis_hashes_map_to_clusters() {
+        let proxy = "https://rpc.example.com";
+        let cases = [
+            (MAINNET_GENESIS_HASH, Cluster::Mainnet),
+            (TESTNET_GENESIS_HASH, Cluster::Testnet),
+            (DEVNET_GENESIS_HASH, Cluster::Devnet),
+            (
+                "11111111111111111111111111111111",
+                Cluster::Custom(proxy.into()),
+            ),
+        ];
+        for (genesis_hash, expected) in cases {
+            assert_eq!(Cluster::from_genesis_hash(genesis_hash, proxy), expected);
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::mint::{FailureCode, MintError, MintRequest, MintResponse};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use tokio::time::Instant;
+
+/// How long a key is remembered after its first use.
+pub const DEFAULT_IDEMPOTENCY_TTL: Duration = Duration::from_secs(3600);
+/// Most keys remembered at once; the oldest is forgotten first.
+pub const DEFAULT_IDEMPOTENCY_MAX_KEYS: usize = 10_000;
+
+/// What a key resolved to. Only outcomes a retry must not repeat are kept.
+#[derive(Debug)]
+enum Outcome {
+    Minted(Box<MintResponse>),
+    /// The mint timed out, so its transaction may still land.
+    Pending(Option<String>),
+}
+
+type Slot = Arc<OnceCell<Outcome>>;
+
+struct Entry {
+    created: Instant,
+    request_hash: [u8; 32],
+    slot: Slot,
+}
+
+#[derive(Default)]
+struct Slots {
+    by_key: HashMap<String, Entry>,
+    /// Keys in insertion order, so expiry and eviction only touch the
+    /// oldest entries.
+    order: VecDeque<(Instant, String)>,
+}
+
+impl Slots {
+    fn remove_oldest(&mut self) {
+        if let Some((created, key)) = self.order.pop_front() {
+            // The key may have expired and been reused since.
+            if self.by_key.get(&key).is_some_and(|e| e.created == created) {
+                self.by_key.remove(&key);
+            }
+        }
+    }
+}
+
+/// Why `get_or_mint` did not return a response.
+#[derive(Debug)]
+pub enum IdempotencyError {
+    /// The key was first used with a different request.
+    Mismatch,
+    /// The first request with the key timed out; its transaction, if known,
+    /// may still land.
+    Pending(Option<String>),
+    /// The mint failed and nothing was cached, so the key can be retried.
+    Failed(MintError),
+}
+
+/// Responses of recent mints by `Idempotency-Key`, so a client retrying a
+/// request it never heard back from gets the original result instead of a
+/// second transaction.
+pub struct IdempotencyCache {
+    ttl: Duration,
+    max_keys: usize,
+    slots: Mutex<Slots>,
+}
+
+impl Default for IdempotencyCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_IDEMPOTENCY_TTL, DEFAULT_IDEMPOTENCY_MAX_KEYS)
+    }
+}
+
+impl IdempotencyCache {
+    pub fn new(ttl: Duration, max_keys: usize) -> Self {
+        Self {
+            ttl,
+            max_keys: max_keys.max(1),
+            slots: Mutex::new(Slots::default()),
+        }
+    }
+
+    /// The response cached under `key`, or the result of `mint`. A request
+    /// arriving while the first with its key is still in flight waits for
+    /// it. A timed-out mint is remembered as pending, since retrying it
+    /// could mint twice; other failures are not cached.
+    pub async fn get_or_mint<F>(
+        &self,
+        key: &str,
+        req: &MintRequest,
+        mint: F,
+    ) -> Result<MintResponse, IdempotencyError>
+    where
+        F: Future<Output = Result<MintResponse, MintError>>,
+    {
+        let request_hash = hash_request(req);
+        let slot = {
+            let mut slots = self.slots.lock().unwrap();
+            while slots
+                .order
+                .front()
+                .is_some_and(|(created, _)| created.elapsed() >= self.ttl)
+            {
+                slots.remove_oldest();
+            }
+            match slots.by_key.get(key) {
+                Some(entry) if entry.request_hash != request_hash => {
+                    return Err(IdempotencyError::Mismatch)
+                }
+                Some(entry) => entry.slot.clone(),
+                None => {
+                    while slots.by_key.len() >= self.max_keys {
+                        slots.remove_oldest();
+                    }
+                    let created = Instant::now();
+                    let slot = Slot::default();
+                    slots.order.push_back((created, key.to_string()));
+                    slots.by_key.insert(
+                        key.to_string(),
+                        Entry {
+                            created,
+                            request_hash,
+                            slot: slot.clone(),
+                        },
+                    );
+                    slot
+                }
+            }
+        };
+
+        // The caller that timed out gets its own error; later ones see the
+        // pending outcome.
+        let mut timed_out = None;
+        let outcome = slot
+            .get_or_try_init(|| async {
+                match mint.await {
+                    Ok(response) => Ok(Outcome::Minted(Box::new(response))),
+                    Err(e) if e.code == FailureCode::Timeout => {
+                        let pending = Outcome::Pending(e.pending_signature.clone());
+                        timed_out = Some(e);
+                        Ok(pending)
+                    }
+                    Err(e) => Err(e),
+                }
+            })
+            .await
+            .map_err(IdempotencyError::Failed)?;
+        if let Some(e) = timed_out {
+            return Err(IdempotencyError::Failed(e));
+        }
+        match outcome {
+            Outcome::Minted(response) => Ok(MintResponse::clone(response)),
+            Outcome::Pending(signature) => Err(IdempotencyError::Pending(signature.clone())),
+        }
+    }
+}
+
+/// SHA-256 of every field of the request, to tell a retry from a different
+/// request reusing the key. Not the JSON, which leaves out delivery options
+/// such as `dry_run` and `extra_memos`.
+fn hash_request(req: &MintRequest) -> [u8; 32] {
+    Sha256::digest(format!("{req:?}")).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mint::{build_memo, MintConfig};
+
+    fn request() -> MintRequest {
+        MintRequest::builder("iceland-reykjavik").build()
+    }
+
+    fn minted(signature: &str) -> Result<MintResponse, MintError> {
+        Ok(MintResponse {
+            signature: signature.into(),
+            signatures: vec![signature.into()],
+            memo_content: build_memo(&request(), &MintConfig::default()),
+            explorer_url: String::new(),
+            confirmation_level: "confirmed".into(),
+            dry_run: false,
+            memo_data: None,
+        })
+    }
+
+    fn failed(
+        code: FailureCode,
+        pending_signature: Option<&str>,
+    ) -> Result<MintResponse, MintError> {
+        Err(MintError {
+            code,
+            message: "failed".into(),
+            logs: Vec::new(),
+            pending_signature: pending_signature.map(String::from),
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failures_not_cached_and_keys_expire() {
+        let cache = IdempotencyCache::new(Duration::from_secs(60), 100);
+        let req = request();
+        let first = cache.get_or_mint("key", &req, async { minted("sig-1") });
+        assert_eq!(first.await.unwrap().signature, "sig-1");
+        let repeat = cache.get_or_mint("key", &req, async { minted("sig-x") });
+        assert_eq!(repeat.await.unwrap().signature, "sig-1");
+
+        let failed = cache.get_or_mint("other", &req, async { failed(FailureCode::Rpc, None) });
+        assert!(matches!(failed.await, Err(IdempotencyError::Failed(_))));
+        let retried = cache.get_or_mint("other", &req, async { minted("sig-2") });
+        assert_eq!(retried.await.unwrap().signature, "sig-2");
+
+        tokio::time::advance(Duration::from_secs(61)).await;
+        let after = cache.get_or_mint("key", &req, async { minted("sig-3") });
+        assert_eq!(after.await.unwrap().signature, "sig-3");
+    }
+
+    #[tokio::test]
+    async fn test_timeouts_stay_pending_and_keys_bound_to_request() {
+        let cache = IdempotencyCache::new(DEFAULT_IDEMPOTENCY_TTL, 2);
+        let req = request();
+
+        let first = cache.get_or_mint("slow", &req, async {
+            failed(FailureCode::Timeout, Some("sig-pending"))
+        });
+        assert!(
+            matches!(first.await, Err(IdempotencyError::Failed(e)) if e.code == FailureCode::Timeout)
+        );
+        let retry = cache.get_or_mint("slow", &req, async { minted("sig-2") });
+        assert!(matches!(
+            retry.await,
+            Err(IdempotencyError::Pending(Some(s))) if s == "sig-pending"
+        ));
+
+        let other = MintRequest::builder("norway-oslo").build();
+        let reused = cache.get_or_mint("slow", &other, async { minted("sig-3") });
+        assert!(matches!(reused.await, Err(IdempotencyError::Mismatch)));
+        let dry_run = MintRequest::builder("iceland-reykjavik").dry_run().build();
+        let reused = cache.get_or_mint("slow", &dry_run, async { minted("sig-3") });
+        assert!(matches!(reused.await, Err(IdempotencyError::Mismatch)));
+
+        // At the cap the oldest key is forgotten.
+        cache
+            .get_or_mint("b", &req, async { minted("sig-b") })
+            .await
+            .unwrap();
+        cache
+            .get_or_mint("c", &req, async { minted("sig-c") })
+            .await
+            .unwrap();
+        let fresh = cache.get_or_mint("slow", &req, async { minted("sig-4") });
+        assert_eq!(fresh.await.unwrap().signature, "sig-4");
+    }
+}
@@ -0,0 +1,192 @@
+use crate::mint::MintResponse;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+
+const DEFAULT_INDEX_LIMIT: usize = 100;
+
+/// A minted record as stored in the index.
+#[derive(Debug, Serialize)]
+pub struct IndexedRecord {
+    pub signature: String,
+    pub location_id: String,
+    pub timestamp: String,
+    pub feasibility_grade: Option<String>,
+    pub capacity_mw: Option<f64>,
+}
+
+/// Query parameters for `/index/records`.
+#[derive(Debug, Default, Deserialize)]
+pub struct IndexQuery {
+    pub location_id: Option<String>,
+    /// Matches `feasibility_grade` exactly.
+    pub grade: Option<String>,
+    /// Maximum number of records to return.
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexPage {
+    pub records: Vec<IndexedRecord>,
+}
+
+/// SQLite table of successful mints, so listing them does not have to page
+/// through chain history.
+pub struct RecordIndex {
+    conn: Mutex<Connection>,
+}
+
+impl RecordIndex {
+    /// Open (creating if needed) the database at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let conn = Connection::open(path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        Self::init(conn)
+    }
+
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Self::init(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    fn init(conn: Connection) -> Result<Self, String> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS records (
+                signature TEXT PRIMARY KEY,
+                location_id TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                feasibility_grade TEXT,
+                capacity_mw REAL
+            );
+            CREATE INDEX IF NOT EXISTS records_location_id ON records (location_id);",
+        )
+        .map_err(|e| format!("Failed to create index schema: {}", e))?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Store a successful mint. Dry runs are ignored, and recording the same
+    /// signature twice keeps the first row.
+    pub fn insert_record(&self, response: &MintResponse) -> Result<(), String> {
+        if response.dry_run {
+            return Ok(());
+        }
+        let record = &response.memo_content;
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT OR IGNORE INTO records
+                    (signature, location_id, timestamp, feasibility_grade, capacity_mw)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    response.signature,
+                    record.location_id,
+                    record.timestamp,
+                    record.feasibility_grade,
+                    record.capacity_mw,
+                ],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to index {}: {}", response.signature, e))
+    }
+
+    /// Records matching every filter in `query`, most recently indexed first.
+    pub fn query_records(&self, query: &IndexQuery) -> Result<Vec<IndexedRecord>, String> {
+        let limit = query.limit.unwrap_or(DEFAULT_INDEX_LIMIT).max(1);
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT signature, location_id, timestamp, feasibility_grade, capacity_mw
+                 FROM records
+                 WHERE (?1 IS NULL OR location_id = ?1)
+                   AND (?2 IS NULL OR feasibility_grade = ?2)
+                 ORDER BY rowid DESC
+                 LIMIT ?3",
+            )
+            .map_err(|e| format!("Failed to query index: {}", e))?;
+        let rows = stmt
+            .query_map(
+                params![query.location_id, query.grade, limit as i64],
+                |row| {
+                    Ok(IndexedRecord {
+                        signature: row.get(0)?,
+                        location_id: row.get(1)?,
+                        timestamp: row.get(2)?,
+                        feasibility_grade: row.get(3)?,
+                        capacity_mw: row.get(4)?,
+                    })
+                },
+            )
+            .map_err(|e| format!("Failed to query index: {}", e))?;
+        rows.collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read index row: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mint::{build_memo, MintConfig, MintRequest};
+
+    fn minted(signature: &str, location_id: &str, grade: &str) -> MintResponse {
+        let req = MintRequest::builder(location_id)
+            .grade(grade)
+            .capacity_mw(50.0)
+            .build();
+        MintResponse {
+            signature: signature.into(),
+            signatures: vec![signature.into()],
+            memo_content: build_memo(&req, &MintConfig::default()),
+            explorer_url: String::new(),
+            confirmation_level: "confirmed".into(),
+            dry_run: false,
+            memo_data: None,
+        }
+    }
+
+    #[test]
+    fn test_query_filters_by_location_and_grade() {
+        let index = RecordIndex::in_memory();
+        index
+            .insert_record(&minted("sig-1", "iceland-reykjavik", "A"))
+            .unwrap();
+        index
+            .insert_record(&minted("sig-2", "iceland-reykjavik", "B"))
+            .unwrap();
+        index
+            .insert_record(&minted("sig-3", "norway-oslo", "A"))
+            .unwrap();
+        index
+            .insert_record(&minted("sig-1", "iceland-reykjavik", "A"))
+            .unwrap();
+
+        let all = index.query_records(&IndexQuery::default()).unwrap();
+        let signatures: Vec<_> = all.iter().map(|r| r.signature.as_str()).collect();
+        assert_eq!(signatures, ["sig-3", "sig-2", "sig-1"]);
+        assert_eq!(all[0].capacity_mw, Some(50.0));
+
+        let iceland_a = index
+            .query_records(&IndexQuery {
+                location_id: Some("iceland-reykjavik".into()),
+                grade: Some("A".into()),
+                limit: None,
+            })
+            .unwrap();
+        assert_eq!(iceland_a.len(), 1);
+        assert_eq!(iceland_a[0].signature, "sig-1");
+
+        let grade_a = index
+            .query_records(&IndexQuery {
+                grade: Some("A".into()),
+                limit: Some(1),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(grade_a.len(), 1);
+        assert_eq!(grade_a[0].signature, "sig-3");
+    }
+}
@@ -1,15 +1,209 @@
+mod keystore;
+// Shared with the server, which also uses the loaders keygen does not.
+#[allow(dead_code)]
+mod wallet;
+
 use solana_keypair::Keypair;
 use solana_signer::Signer;
 use std::fs;
+use std::io::Write;
 use std::path::Path;
 
+/// Default for `--max-attempts`: a few minutes of searching.
+const DEFAULT_MAX_ATTEMPTS: u64 = 10_000_000;
+/// Each extra character multiplies the expected search by about 29 (58
+/// base58 symbols, halved by ignoring case).
+const SLOW_PREFIX_LEN: usize = 4;
+
+#[derive(Debug, PartialEq)]
+enum Format {
+    /// JSON byte array, written to the wallet file.
+    Json,
+    /// base58 of the 64-byte secret, printed only.
+    Base58,
+}
+
+struct Args {
+    format: Format,
+    /// Also write the wallet file when printing another format.
+    save: bool,
+    /// Write the wallet file encrypted with a password.
+    encrypt: bool,
+    /// Vanity prefix the public key must start with, ignoring case.
+    prefix: Option<String>,
+    /// Keypairs tried for `prefix` before giving up.
+    max_attempts: u64,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args {
+        format: Format::Json,
+        save: false,
+        encrypt: false,
+        prefix: None,
+        max_attempts: DEFAULT_MAX_ATTEMPTS,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                parsed.format = match args.next().as_deref() {
+                    Some("json") => Format::Json,
+                    Some("base58") => Format::Base58,
+                    other => return Err(format!("Unknown --format {:?} (json, base58)", other)),
+                }
+            }
+            "--save" => parsed.save = true,
+            "--encrypt" => parsed.encrypt = true,
+            "--prefix" => {
+                let prefix = args.next().ok_or("--prefix needs a value")?;
+                // Every other character appears in base58 in at least one case.
+                if prefix.is_empty()
+                    || !prefix
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() && c != '0')
+                {
+                    return Err(format!(
+                        "Prefix {:?} can never match a base58 address",
+                        prefix
+                    ));
+                }
+                parsed.prefix = Some(prefix);
+            }
+            "--max-attempts" => {
+                parsed.max_attempts = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or("--max-attempts needs a number")?;
+            }
+            other => return Err(format!("Unknown argument {:?}", other)),
+        }
+    }
+    Ok(parsed)
+}
+
+/// The 64-byte secret in the base58 form `solana-keygen` and browser wallets
+/// import.
+fn base58_secret(keypair: &Keypair) -> String {
+    keypair.to_base58_string()
+}
+
+/// Generate keypairs until one's public key starts with `prefix`, ignoring
+/// case. Returns it with the number of attempts taken.
+fn vanity_keypair(prefix: &str, max_attempts: u64) -> Result<(Keypair, u64), String> {
+    let prefix = prefix.to_lowercase();
+    for attempt in 1..=max_attempts {
+        let keypair = Keypair::new();
+        if keypair
+            .pubkey()
+            .to_string()
+            .to_lowercase()
+            .starts_with(&prefix)
+        {
+            return Ok((keypair, attempt));
+        }
+    }
+    Err(format!(
+        "No address starting with {:?} after {} attempts",
+        prefix, max_attempts
+    ))
+}
+
+/// A fresh keypair, matching `--prefix` when one was given. Exits on
+/// failure.
+fn new_keypair(args: &Args) -> Keypair {
+    let Some(prefix) = &args.prefix else {
+        return Keypair::new();
+    };
+    if prefix.len() > SLOW_PREFIX_LEN {
+        eprintln!(
+            "Warning: prefixes longer than {} characters can take a very long time.",
+            SLOW_PREFIX_LEN
+        );
+    }
+    match vanity_keypair(prefix, args.max_attempts) {
+        Ok((keypair, attempts)) => {
+            println!("Found a {:?} address after {} attempts.", prefix, attempts);
+            keypair
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Write `keypair` as a JSON byte array or, with `encrypt`, encrypted with
+/// a password from `WALLET_PASSWORD` or the terminal. Exits on failure.
+fn write_wallet(keypair: &Keypair, wallet_path: &Path, encrypt: bool) {
+    let contents = if encrypt {
+        keystore::password("New wallet password: ")
+            .and_then(|password| keystore::encrypt(&keypair.to_bytes(), &password))
+            .unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            })
+    } else {
+        let bytes: Vec<u8> = keypair.to_bytes().to_vec();
+        serde_json::to_string(&bytes).expect("Failed to serialize keypair")
+    };
+    write_secret_file(wallet_path, &contents).expect("Failed to write wallet file");
+}
+
+/// Write `contents` to `path`, creating it readable by its owner only on
+/// Unix, so the server does not warn about the wallet's permissions.
+fn write_secret_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(contents.as_bytes())
+}
+
+/// Read the existing wallet file in any format the server accepts,
+/// unlocking it if it is encrypted. Exits on failure, e.g. a wrong password.
+fn read_wallet(wallet_path: &Path) -> Keypair {
+    wallet::load_wallet(wallet_path).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    })
+}
+
 fn main() {
+    let args = parse_args(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        eprintln!(
+            "Usage: keygen [--format json|base58] [--save] [--encrypt] [--prefix <str>] [--max-attempts <n>]"
+        );
+        std::process::exit(2);
+    });
     let wallet_path = Path::new("devnet-wallet.json");
 
+    if args.format == Format::Base58 {
+        if args.save && wallet_path.exists() {
+            eprintln!("{} already exists; not overwriting.", wallet_path.display());
+            std::process::exit(1);
+        }
+        let keypair = new_keypair(&args);
+        println!("New keypair generated:");
+        println!("  Public key: {}", keypair.pubkey());
+        println!("  Secret (base58): {}", base58_secret(&keypair));
+        if args.save {
+            write_wallet(&keypair, wallet_path, args.encrypt);
+            println!("  Saved to: {}", wallet_path.display());
+        } else {
+            println!(
+                "\nNot saved. Pass --save to also write {}.",
+                wallet_path.display()
+            );
+        }
+        return;
+    }
+
     if wallet_path.exists() {
-        let data = fs::read_to_string(wallet_path).expect("Failed to read wallet file");
-        let bytes: Vec<u8> = serde_json::from_str(&data).expect("Invalid wallet JSON");
-        let kp = Keypair::try_from(bytes.as_slice()).expect("Invalid keypair");
+        let kp = read_wallet(wallet_path);
         println!("Wallet already exists:");
         println!("  Public key: {}", kp.pubkey());
         println!("  File: {}", wallet_path.display());
@@ -17,13 +211,63 @@ fn main() {
         return;
     }
 
-    let keypair = Keypair::new();
-    let bytes: Vec<u8> = keypair.to_bytes().to_vec();
-    let json = serde_json::to_string(&bytes).expect("Failed to serialize keypair");
-    fs::write(wallet_path, json).expect("Failed to write wallet file");
+    let keypair = new_keypair(&args);
+    write_wallet(&keypair, wallet_path, args.encrypt);
 
     println!("New devnet wallet generated:");
     println!("  Public key: {}", keypair.pubkey());
     println!("  Saved to: {}", wallet_path.display());
     println!("\nNext: fund it at https://faucet.solana.com");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base58_secret_round_trips() {
+        let keypair = Keypair::new();
+        let decoded = Keypair::from_base58_string(&base58_secret(&keypair));
+        assert_eq!(decoded.to_bytes(), keypair.to_bytes());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_wallet_file_readable_by_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("keygen-wallet-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        write_secret_file(&path, "[1, 2, 3]").unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        fs::remove_file(&path).unwrap();
+        assert_eq!(mode, 0o600);
+    }
+
+    #[test]
+    fn test_parse_format_flag() {
+        let args = parse_args(["--format", "base58"].map(String::from).into_iter()).unwrap();
+        assert_eq!(args.format, Format::Base58);
+        assert!(!args.save);
+        assert!(!args.encrypt);
+        let args = parse_args(["--save", "--encrypt"].map(String::from).into_iter()).unwrap();
+        assert!(args.save && args.encrypt);
+        assert!(parse_args(["--format", "hex"].map(String::from).into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_vanity_prefix_matches_ignoring_case() {
+        let args = parse_args(["--prefix", "dC"].map(String::from).into_iter()).unwrap();
+        assert_eq!(args.prefix.as_deref(), Some("dC"));
+        assert!(parse_args(["--prefix", "d0"].map(String::from).into_iter()).is_err());
+
+        let (keypair, attempts) = vanity_keypair("dC", args.max_attempts).unwrap();
+        assert!(keypair
+            .pubkey()
+            .to_string()
+            .to_lowercase()
+            .starts_with("dc"));
+        assert!(attempts >= 1);
+        assert!(vanity_keypair("dC", 0).is_err());
+    }
+}
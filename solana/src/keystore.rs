@@ -0,0 +1,163 @@
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// First line of an encrypted wallet file, so it is never mistaken for a
+/// plaintext one.
+const MAGIC: &str = "orbital-atlas-encrypted-wallet v1\n";
+/// scrypt cost (`N = 2^log_n`, r = 8, p = 1): about 32 MiB and a fraction
+/// of a second per unlock.
+const DEFAULT_LOG_N: u8 = 15;
+/// Most memory (`128 * r * 2^log_n` bytes) and work (`p * r * 2^log_n`)
+/// `decrypt` lets a wallet file's KDF parameters ask for, so a crafted file
+/// cannot make unlocking take gigabytes of memory or hours of CPU. Both are
+/// 8 times the default's.
+const MAX_KDF_MEMORY: u128 = 256 << 20;
+const MAX_KDF_WORK: u128 = 1 << 21;
+
+/// Everything after `MAGIC`. The KDF parameters are stored so they can be
+/// raised later without breaking existing files.
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedWallet {
+    log_n: u8,
+    r: u32,
+    p: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Whether `data` is an encrypted wallet rather than a plaintext secret.
+pub fn is_encrypted(data: &str) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Encrypt `secret` with a key derived from `password` by scrypt, sealed
+/// with AES-256-GCM.
+// Only `keygen` writes encrypted wallets; the server just reads them.
+#[allow(dead_code)]
+pub fn encrypt(secret: &[u8], password: &str) -> Result<String, String> {
+    encrypt_with_cost(secret, password, DEFAULT_LOG_N)
+}
+
+/// `encrypt` with scrypt cost `log_n`; tests use a cheap one.
+pub(crate) fn encrypt_with_cost(
+    secret: &[u8],
+    password: &str,
+    log_n: u8,
+) -> Result<String, String> {
+    let mut rng = rand::rng();
+    let salt: [u8; 16] = rng.random();
+    let nonce: [u8; 12] = rng.random();
+    let (r, p) = (8, 1);
+    let cipher = cipher(password, &salt, log_n, r, p)?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), secret)
+        .map_err(|_| "Failed to encrypt wallet".to_string())?;
+    let body = serde_json::to_string(&EncryptedWallet {
+        log_n,
+        r,
+        p,
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+    .map_err(|e| format!("Failed to serialize encrypted wallet: {}", e))?;
+    Ok(format!("{}{}\n", MAGIC, body))
+}
+
+/// Recover the secret from an encrypted wallet. A wrong password and a
+/// tampered file are indistinguishable and give the same error.
+pub fn decrypt(data: &str, password: &str) -> Result<Vec<u8>, String> {
+    let body = data.strip_prefix(MAGIC).ok_or("Not an encrypted wallet")?;
+    let wallet: EncryptedWallet = serde_json::from_str(body.trim())
+        .map_err(|e| format!("Malformed encrypted wallet: {}", e))?;
+    let decode = |field: &str, value: &str| {
+        BASE64
+            .decode(value)
+            .map_err(|e| format!("Malformed encrypted wallet {}: {}", field, e))
+    };
+    let salt = decode("salt", &wallet.salt)?;
+    let nonce = decode("nonce", &wallet.nonce)?;
+    let ciphertext = decode("ciphertext", &wallet.ciphertext)?;
+    if nonce.len() != 12 {
+        return Err(format!(
+            "Malformed encrypted wallet nonce: {} bytes, expected 12",
+            nonce.len()
+        ));
+    }
+    let blocks = (wallet.log_n < 64).then(|| u128::from(wallet.r) << wallet.log_n);
+    if blocks.is_none_or(|blocks| {
+        128 * blocks > MAX_KDF_MEMORY || u128::from(wallet.p) * blocks > MAX_KDF_WORK
+    }) {
+        return Err(format!(
+            "Malformed encrypted wallet: scrypt log_n={} r={} p={} costs too much to unlock",
+            wallet.log_n, wallet.r, wallet.p
+        ));
+    }
+
+    cipher(password, &salt, wallet.log_n, wallet.r, wallet.p)?
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| "Wrong wallet password, or the wallet file is corrupted".to_string())
+}
+
+fn cipher(password: &str, salt: &[u8], log_n: u8, r: u32, p: u32) -> Result<Aes256Gcm, String> {
+    let params = scrypt::Params::new(log_n, r, p, 32)
+        .map_err(|e| format!("Invalid scrypt parameters: {}", e))?;
+    let mut key = [0u8; 32];
+    scrypt::scrypt(password.as_bytes(), salt, &params, &mut key)
+        .map_err(|e| format!("Failed to derive wallet key: {}", e))?;
+    Aes256Gcm::new_from_slice(&key).map_err(|e| format!("Invalid wallet key: {}", e))
+}
+
+/// The wallet password: `WALLET_PASSWORD` if set, otherwise asked for on
+/// the terminal.
+pub fn password(prompt: &str) -> Result<String, String> {
+    match std::env::var("WALLET_PASSWORD") {
+        Ok(password) => Ok(password),
+        Err(_) => rpassword::prompt_password(prompt).map_err(|e| {
+            format!(
+                "Wallet is encrypted; set WALLET_PASSWORD or run interactively ({})",
+                e
+            )
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_wrong_password() {
+        let secret = [7u8; 64];
+        let data = encrypt_with_cost(&secret, "hunter2", 4).unwrap();
+        assert!(is_encrypted(&data));
+        assert!(!data.contains(&BASE64.encode(secret)));
+
+        assert_eq!(decrypt(&data, "hunter2").unwrap(), secret);
+        let err = decrypt(&data, "hunter3").unwrap_err();
+        assert!(err.starts_with("Wrong wallet password"), "{}", err);
+        assert!(decrypt("[1, 2, 3]", "hunter2").is_err());
+    }
+
+    #[test]
+    fn test_excessive_kdf_cost_rejected() {
+        let data = encrypt_with_cost(&[7u8; 64], "hunter2", 4).unwrap();
+        for (from, to) in [
+            ("\"log_n\":4", "\"log_n\":40"),
+            ("\"r\":8", "\"r\":1048576"),
+            ("\"p\":1", "\"p\":100000"),
+            // Each within a per-parameter cap, but about 4 GiB together.
+            (
+                "\"log_n\":4,\"r\":8,\"p\":1",
+                "\"log_n\":20,\"r\":32,\"p\":16",
+            ),
+        ] {
+            let err = decrypt(&data.replace(from, to), "hunter2").unwrap_err();
+            assert!(err.starts_with("Malformed encrypted wallet"), "{}", err);
+        }
+    }
+}
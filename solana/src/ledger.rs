@@ -0,0 +1,190 @@
+use crate::mint::{MemoRecord, MintResponse};
+use crate::telemetry;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// A record minted by this instance.
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerEntry {
+    pub signature: String,
+    pub signatures: Vec<String>,
+    pub record: MemoRecord,
+    pub minted_at: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct LedgerQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LedgerPage {
+    pub entries: Vec<LedgerEntry>,
+    /// Pass as `cursor` to fetch the next page; absent once exhausted.
+    pub next_cursor: Option<String>,
+}
+
+const DEFAULT_LEDGER_LIMIT: usize = 100;
+/// Largest page `page` returns, whatever `limit` asks for.
+const MAX_LEDGER_LIMIT: usize = 1000;
+
+/// In-memory log of successful mints, oldest first. Lost on restart; the
+/// chain remains the source of truth.
+#[derive(Default)]
+pub struct Ledger {
+    entries: Mutex<Vec<LedgerEntry>>,
+}
+
+impl Ledger {
+    /// Log a mint. Dry runs wrote nothing to the chain and are ignored.
+    pub fn record(&self, response: &MintResponse) {
+        if response.dry_run {
+            return;
+        }
+        let now = Utc::now();
+        self.entries.lock().unwrap().push(LedgerEntry {
+            signature: response.signature.clone(),
+            signatures: response.signatures.clone(),
+            record: response.memo_content.clone(),
+            minted_at: now.to_rfc3339(),
+        });
+        telemetry::record_mint(now.timestamp());
+    }
+
+    /// Unix time of the most recent mint, if any.
+    pub fn last_minted_at(&self) -> Option<i64> {
+        let entries = self.entries.lock().unwrap();
+        let minted_at = &entries.last()?.minted_at;
+        DateTime::parse_from_rfc3339(minted_at)
+            .ok()
+            .map(|t| t.timestamp())
+    }
+
+    /// Signature of the most recent record minted for `location_id`.
+    pub fn latest_signature(&self, location_id: &str) -> Option<String> {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|entry| entry.record.location_id == location_id)
+            .map(|entry| entry.signature.clone())
+    }
+
+    /// The `n` most recent entries, oldest first.
+    pub fn recent(&self, n: usize) -> Vec<LedgerEntry> {
+        let entries = self.entries.lock().unwrap();
+        entries[entries.len().saturating_sub(n)..].to_vec()
+    }
+
+    /// A page of entries starting at `query.cursor`. Cursors are opaque to
+    /// clients; internally they encode the offset of the next entry.
+    pub fn page(&self, query: &LedgerQuery) -> Result<LedgerPage, String> {
+        let start = match &query.cursor {
+            Some(cursor) => decode_cursor(cursor)?,
+            None => 0,
+        };
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_LEDGER_LIMIT)
+            .clamp(1, MAX_LEDGER_LIMIT);
+
+        let entries = self.entries.lock().unwrap();
+        let end = start.saturating_add(limit).min(entries.len());
+        let page = entries.get(start..end).unwrap_or_default().to_vec();
+
+        Ok(LedgerPage {
+            entries: page,
+            next_cursor: (end < entries.len()).then(|| encode_cursor(end)),
+        })
+    }
+}
+
+fn encode_cursor(offset: usize) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(offset.to_string())
+}
+
+fn decode_cursor(cursor: &str) -> Result<usize, String> {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .and_then(|offset| offset.parse().ok())
+        .ok_or_else(|| format!("Invalid cursor: {}", cursor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mint::{build_memo, MintConfig, MintRequest};
+
+    fn response(i: usize) -> MintResponse {
+        let req = MintRequest {
+            location_id: format!("location-{}", i),
+            ..Default::default()
+        };
+        MintResponse {
+            signature: format!("sig-{}", i),
+            signatures: vec![format!("sig-{}", i)],
+            memo_content: build_memo(&req, &MintConfig::default()),
+            explorer_url: String::new(),
+            confirmation_level: "confirmed".into(),
+            dry_run: false,
+            memo_data: None,
+        }
+    }
+
+    #[test]
+    fn test_ledger_pages_without_overlap() {
+        let ledger = Ledger::default();
+        for i in 0..5 {
+            ledger.record(&response(i));
+        }
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = ledger
+                .page(&LedgerQuery {
+                    cursor,
+                    limit: Some(2),
+                })
+                .unwrap();
+            seen.extend(page.entries.into_iter().map(|e| e.signature));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        let expected: Vec<String> = (0..5).map(|i| format!("sig-{}", i)).collect();
+        assert_eq!(seen, expected);
+        assert!(ledger
+            .page(&LedgerQuery {
+                cursor: Some("???".into()),
+                limit: None
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn test_huge_limit_clamped_without_overflow() {
+        let ledger = Ledger::default();
+        for i in 0..3 {
+            ledger.record(&response(i));
+        }
+
+        let page = ledger
+            .page(&LedgerQuery {
+                cursor: Some(encode_cursor(1)),
+                limit: Some(usize::MAX),
+            })
+            .unwrap();
+        let signatures: Vec<&str> = page.entries.iter().map(|e| e.signature.as_str()).collect();
+        assert_eq!(signatures, ["sig-1", "sig-2"]);
+        assert_eq!(page.next_cursor, None);
+    }
+}
@@ -1,17 +1,58 @@
+mod blockhash_cache;
+mod config;
+mod dead_letter;
+mod decode;
+mod explorer;
+mod idempotency;
+mod index;
+mod keystore;
+mod ledger;
+mod manifest;
 mod mint;
+#[cfg(test)]
+mod mock_rpc;
+mod records;
 mod rpc;
+mod sequence;
+mod spl;
+mod telemetry;
+mod verify;
 mod wallet;
 
-use axum::{extract::State, http::StatusCode, routing::{get, post}, Json, Router};
+use axum::{extract::{Path, Query, Request, State}, http::{header, HeaderMap, HeaderValue, StatusCode}, middleware::{self, Next}, response::{IntoResponse, Response}, routing::{get, post}, Json, Router};
+use mint::LAMPORTS_PER_SOL;
 use solana_keypair::Keypair;
 use solana_signer::Signer;
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use tower_http::cors::CorsLayer;
 
 struct AppState {
+    rpc: rpc::RpcClient,
+    /// Separate endpoint for read-only queries, if configured.
+    query_rpc: Option<rpc::RpcClient>,
     rpc_url: String,
     payer: Keypair,
+    /// Signs the memos in place of `payer`, which still pays the fees.
+    author: Option<Keypair>,
+    mint_config: mint::MintConfig,
+    sequences: sequence::SequenceTracker,
+    ledger: ledger::Ledger,
+    /// Blockhash shared by concurrent mints.
+    blockhashes: blockhash_cache::BlockhashCache,
+    /// Where failed mints are recorded, if configured.
+    dead_letters: Option<dead_letter::DeadLetterLog>,
+    /// SQLite index of successful mints backing `/index/records`, if
+    /// configured.
+    index: Option<Arc<index::RecordIndex>>,
+    /// Responses of recent `/mint` requests by `Idempotency-Key`.
+    idempotency: idempotency::IdempotencyCache,
+    /// Indent JSON responses unless a request asks otherwise.
+    pretty_json: bool,
+    /// Seconds proxies may cache a `/record/:signature` hit; 0 disables it.
+    record_max_age: u64,
 }
 
 #[derive(serde::Serialize)]
@@ -20,48 +61,919 @@ struct HealthResponse {
     wallet: String,
     rpc_url: String,
     balance_sol: Option<f64>,
+    /// Unix time of the last successful mint since startup.
+    last_mint_unix_ts: Option<i64>,
+    /// Cluster transaction count (`getTransactionCount`), when available.
+    tx_count: Option<u64>,
+}
+
+#[derive(serde::Serialize)]
+struct BalanceResponse {
+    pubkey: String,
+    lamports: u64,
+    sol: f64,
+}
+
+#[derive(serde::Deserialize)]
+struct AirdropRequest {
+    lamports: u64,
+    /// Recipient; defaults to the payer.
+    pubkey: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct AirdropResponse {
+    signature: String,
+    pubkey: String,
+    lamports: u64,
+    confirmation_level: String,
+}
+
+#[derive(serde::Deserialize)]
+struct ExistsQuery {
+    /// Also search the wallet's on-chain history when the ledger has no
+    /// record, e.g. for mints made before a restart.
+    #[serde(default)]
+    scan_chain: bool,
+}
+
+#[derive(serde::Serialize)]
+struct ExistsResponse {
+    exists: bool,
+    latest_signature: Option<String>,
+}
+
+/// Non-secret effective configuration, so clients need not hardcode limits.
+#[derive(serde::Serialize)]
+struct ConfigResponse {
+    memo_max_bytes: usize,
+    max_memo_chunks: usize,
+    max_batch_size: usize,
+    max_extra_memos: usize,
+    max_measured_age_secs: i64,
+    max_future_skew_secs: i64,
+    blockhash_commitment: rpc::CommitmentLevel,
+    default_confirmation: rpc::CommitmentLevel,
+    log_anchor: Option<String>,
+    schema_url: Option<String>,
+    hash_algo: mint::HashAlgo,
+    supported_encodings: Vec<&'static str>,
+    /// Whether memo data starts with a `vXX:` version discriminator.
+    version_discriminator: bool,
+    sla_profiles: Vec<&'static str>,
+}
+
+#[derive(serde::Serialize)]
+struct EndpointStatus {
+    role: &'static str,
+    url: String,
+    healthy: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct ReadinessResponse {
+    ready: bool,
+    endpoints: Vec<EndpointStatus>,
 }
 
 #[derive(serde::Serialize)]
 struct ErrorResponse {
     error: String,
+    /// Program logs explaining a failed transaction, when there are any.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    logs: Vec<String>,
+    /// Whether repeating the request unchanged may succeed, e.g. after a
+    /// network failure or timeout rather than a validation error.
+    error_retryable: bool,
+    /// Transaction that was sent but not confirmed in time and may still
+    /// land; check it before minting again.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pending_signature: Option<String>,
+}
+
+impl ErrorResponse {
+    fn new(error: String) -> Self {
+        Self {
+            error,
+            logs: Vec::new(),
+            error_retryable: false,
+            pending_signature: None,
+        }
+    }
 }
 
-async fn health(State(state): State<Arc<AppState>>) -> Json<HealthResponse> {
-    let rpc = rpc::RpcClient::new(&state.rpc_url);
-    let balance = rpc
-        .get_balance(&state.payer.pubkey())
+impl From<mint::MintError> for ErrorResponse {
+    fn from(e: mint::MintError) -> Self {
+        Self {
+            error: e.message,
+            logs: e.logs,
+            error_retryable: e.code.is_retryable(),
+            pending_signature: e.pending_signature,
+        }
+    }
+}
+
+impl From<rpc::RpcError> for ErrorResponse {
+    fn from(e: rpc::RpcError) -> Self {
+        Self {
+            logs: e.logs(),
+            error: e.to_string(),
+            error_retryable: e.is_retryable(),
+            pending_signature: None,
+        }
+    }
+}
+
+/// Respond to a failed mint with a status matching its cause.
+fn mint_error(e: mint::MintError) -> (StatusCode, Json<ErrorResponse>) {
+    let status = match e.code {
+        // The transaction may still land, so this is not the client's
+        // fault; `CONFIRM_TIMEOUT_BEHAVIOR=accept` avoids it entirely.
+        mint::FailureCode::Timeout => StatusCode::GATEWAY_TIMEOUT,
+        mint::FailureCode::Network => StatusCode::BAD_GATEWAY,
+        _ => StatusCode::BAD_REQUEST,
+    };
+    (status, Json(ErrorResponse::from(e)))
+}
+
+/// Respond to a failed RPC read. Everything but a timeout is reported as
+/// the upstream node's failure.
+fn rpc_error(e: rpc::RpcError) -> (StatusCode, Json<ErrorResponse>) {
+    let status = match e {
+        rpc::RpcError::Timeout(_) | rpc::RpcError::Expired(_) => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::BAD_GATEWAY,
+    };
+    (status, Json(ErrorResponse::from(e)))
+}
+
+/// Balance lookups tried before `/health` reports degraded, so a single
+/// transient RPC error does not flap alerts.
+const HEALTH_BALANCE_ATTEMPTS: usize = 2;
+
+/// Seconds a found record may be cached by default. Records never change
+/// once they land.
+const DEFAULT_RECORD_MAX_AGE_SECS: u64 = 86_400;
+
+/// `Cache-Control` for a response that must always be fetched fresh.
+const NO_STORE: [(header::HeaderName, HeaderValue); 1] =
+    [(header::CACHE_CONTROL, HeaderValue::from_static("no-store"))];
+
+async fn health(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let mut balance = None;
+    for attempt in 1..=HEALTH_BALANCE_ATTEMPTS {
+        match state.rpc.get_balance(&state.payer.pubkey()).await {
+            Ok(lamports) => {
+                let sol = lamports as f64 / LAMPORTS_PER_SOL;
+                telemetry::record_payer_balance(sol);
+                balance = Some(sol);
+                break;
+            }
+            Err(e) => tracing::warn!(
+                "Health balance check failed (attempt {}/{}): {}",
+                attempt,
+                HEALTH_BALANCE_ATTEMPTS,
+                e
+            ),
+        }
+    }
+
+    let tx_count = state
+        .rpc
+        .get_transaction_count()
         .await
-        .ok()
-        .map(|lamports| lamports as f64 / 1_000_000_000.0);
+        .inspect_err(|e| tracing::warn!("Health transaction count failed: {}", e))
+        .ok();
+
+    (
+        NO_STORE,
+        Json(HealthResponse {
+            status: if balance.is_some() { "ok" } else { "degraded" }.into(),
+            wallet: state.payer.pubkey().to_string(),
+            rpc_url: state.rpc_url.clone(),
+            balance_sol: balance,
+            last_mint_unix_ts: state.ledger.last_minted_at(),
+            tx_count,
+        }),
+    )
+}
 
-    Json(HealthResponse {
-        status: "ok".into(),
-        wallet: state.payer.pubkey().to_string(),
-        rpc_url: state.rpc_url.clone(),
-        balance_sol: balance,
+/// Longest startup waits on a warmup request before serving anyway.
+const WARMUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Open a connection to `rpc` with a cheap `getHealth`, so the first mint
+/// does not pay for the TLS handshake. Failures are only logged.
+async fn warm_up(rpc: &rpc::RpcClient, timeout: Duration) {
+    let started = std::time::Instant::now();
+    match tokio::time::timeout(timeout, rpc.get_health()).await {
+        Ok(Ok(())) => tracing::info!(
+            "Warmed up {} in {}ms",
+            rpc.url(),
+            started.elapsed().as_millis()
+        ),
+        Ok(Err(e)) => tracing::warn!("Warmup of {} failed: {}", rpc.url(), e),
+        Err(_) => tracing::warn!("Warmup of {} timed out after {:?}", rpc.url(), timeout),
+    }
+}
+
+/// Ready only when every configured RPC endpoint reports healthy.
+async fn readyz(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let mut endpoints = vec![("primary", &state.rpc)];
+    if let Some(query_rpc) = &state.query_rpc {
+        endpoints.push(("query", query_rpc));
+    }
+
+    let mut statuses = Vec::with_capacity(endpoints.len());
+    for (role, rpc) in endpoints {
+        let result = rpc.get_health().await;
+        statuses.push(EndpointStatus {
+            role,
+            url: rpc.url().to_string(),
+            healthy: result.is_ok(),
+            error: result.err().map(|e| e.to_string()),
+        });
+    }
+
+    let ready = statuses.iter().all(|s| s.healthy);
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        NO_STORE,
+        Json(ReadinessResponse {
+            ready,
+            endpoints: statuses,
+        }),
+    )
+}
+
+/// Prometheus text exposition of the process metrics.
+async fn metrics_handler() -> String {
+    telemetry::install().render()
+}
+
+async fn config_handler(State(state): State<Arc<AppState>>) -> Json<ConfigResponse> {
+    let config = &state.mint_config;
+    Json(ConfigResponse {
+        memo_max_bytes: mint::MEMO_MAX_BYTES,
+        max_memo_chunks: config.max_memo_chunks,
+        max_batch_size: config.max_batch_size,
+        max_extra_memos: config.max_extra_memos,
+        max_measured_age_secs: config.max_measured_age_secs,
+        max_future_skew_secs: config.max_future_skew_secs,
+        blockhash_commitment: state.rpc.blockhash_commitment(),
+        default_confirmation: rpc::CommitmentLevel::Processed,
+        log_anchor: config.log_anchor.clone(),
+        schema_url: config.schema_url.clone(),
+        hash_algo: config.hash_algo,
+        supported_encodings: config.memo_encodings(),
+        version_discriminator: config.version_discriminator,
+        sla_profiles: vec!["reliable", "fast"],
     })
 }
 
+/// Mints `req`, or with an `Idempotency-Key` header returns the response of
+/// the earlier request with that key, if any. Reusing a key for a different
+/// request is rejected with 422, and retrying one whose mint timed out with
+/// 409, since its transaction may still land.
 async fn mint_handler(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(req): Json<mint::MintRequest>,
 ) -> Result<Json<mint::MintResponse>, (StatusCode, Json<ErrorResponse>)> {
-    let rpc = rpc::RpcClient::new(&state.rpc_url);
+    let key = headers
+        .get("idempotency-key")
+        .and_then(|value| value.to_str().ok())
+        .filter(|key| !key.is_empty());
+    let Some(key) = key else {
+        return mint_and_record(&state, req)
+            .await
+            .map(Json)
+            .map_err(mint_error);
+    };
+    let request = req.clone();
+    state
+        .idempotency
+        .get_or_mint(key, &request, mint_and_record(&state, req))
+        .await
+        .map(Json)
+        .map_err(|e| match e {
+            idempotency::IdempotencyError::Mismatch => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ErrorResponse::new(format!(
+                    "Idempotency-Key {:?} was already used with a different request",
+                    key
+                ))),
+            ),
+            idempotency::IdempotencyError::Pending(signature) => {
+                let error = match &signature {
+                    Some(signature) => format!(
+                        "An earlier request with this Idempotency-Key timed out; transaction {} may still land",
+                        signature
+                    ),
+                    None => "An earlier request with this Idempotency-Key timed out and may still land".into(),
+                };
+                (
+                    StatusCode::CONFLICT,
+                    Json(ErrorResponse {
+                        pending_signature: signature,
+                        ..ErrorResponse::new(error)
+                    }),
+                )
+            }
+            idempotency::IdempotencyError::Failed(e) => mint_error(e),
+        })
+}
+
+/// Run blocking file or database work off the async runtime.
+async fn blocking<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> T {
+    tokio::task::spawn_blocking(work)
+        .await
+        .expect("blocking task panicked")
+}
+
+/// Add a successful mint to the ledger and, if configured, the index.
+async fn record_success(state: &AppState, response: &mint::MintResponse) {
+    state.ledger.record(response);
+    if let Some(index) = &state.index {
+        let (index, response) = (index.clone(), response.clone());
+        if let Err(e) = blocking(move || index.insert_record(&response)).await {
+            tracing::error!("Failed to index record: {}", e);
+        }
+    }
+}
+
+/// Mint `req`, adding it to the ledger on success and to the dead-letter
+/// log on failure.
+async fn mint_and_record(
+    state: &AppState,
+    req: mint::MintRequest,
+) -> Result<mint::MintResponse, mint::MintError> {
+    let dead_letter = state
+        .dead_letters
+        .as_ref()
+        .map(|log| (log.clone(), req.clone()));
+    let result = mint::mint_sequenced(
+        &state.rpc,
+        &state.payer,
+        state.author.as_ref(),
+        req,
+        &state.mint_config,
+        &state.sequences,
+        &state.blockhashes,
+    )
+    .await;
+
+    match result {
+        Ok(response) => {
+            record_success(state, &response).await;
+            Ok(response)
+        }
+        Err(e) => {
+            if let Some((log, req)) = dead_letter {
+                let error = e.clone();
+                if let Err(write_err) = blocking(move || log.record(req, &error)).await {
+                    tracing::error!("Failed to record dead letter: {}", write_err);
+                }
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Retry a mint from the dead-letter log. A replay that fails again is
+/// appended as a new entry. An entry already replayed, or whose timed-out
+/// transaction turns out to have landed, is marked resolved and rejected
+/// with 409 instead of minting again.
+async fn dead_letter_replay_handler(
+    State(state): State<Arc<AppState>>,
+    Json(replay): Json<dead_letter::ReplayRequest>,
+) -> Result<Json<mint::MintResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let not_found = |error: String| (StatusCode::NOT_FOUND, Json(ErrorResponse::new(error)));
+    let internal = |error: String| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse::new(error)),
+        )
+    };
+    let conflict = |error: String| (StatusCode::CONFLICT, Json(ErrorResponse::new(error)));
+    let Some(log) = &state.dead_letters else {
+        return Err(not_found("Dead-letter log is not configured".into()));
+    };
+    let index = replay.index;
+    let _replaying = log.lock_replays().await;
+    let entry = blocking({
+        let log = log.clone();
+        move || log.get(index)
+    })
+    .await
+    .map_err(internal)?
+    .ok_or_else(|| not_found(format!("No dead letter at index {}", index)))?;
 
-    match mint::mint(&rpc, &state.payer, &req).await {
-        Ok(response) => Ok(Json(response)),
-        Err(e) => Err((
+    if let Some(signature) = entry.replayed_signature {
+        return Err(conflict(format!(
+            "Dead letter {} was already replayed as {}",
+            index, signature
+        )));
+    }
+    if let Some(signature) = entry.pending_signature {
+        let landed = state
+            .rpc
+            .get_transaction(&signature)
+            .await
+            .map_err(rpc_error)?
+            .is_some_and(|tx| tx["meta"]["err"].is_null());
+        if landed {
+            let log = log.clone();
+            let marked = signature.clone();
+            blocking(move || log.mark_replayed(index, &marked))
+                .await
+                .map_err(internal)?;
+            return Err(conflict(format!(
+                "Dead letter {} timed out but its transaction {} has landed",
+                index, signature
+            )));
+        }
+    }
+
+    let response = mint_and_record(&state, entry.request)
+        .await
+        .map_err(mint_error)?;
+    if !response.dry_run {
+        let log = log.clone();
+        let signature = response.signature.clone();
+        if let Err(e) = blocking(move || log.mark_replayed(index, &signature)).await {
+            // The mint went through; failing now would invite a second one.
+            tracing::error!("Failed to mark dead letter {} replayed: {}", index, e);
+        }
+    }
+    Ok(Json(response))
+}
+
+async fn mint_batch_handler(
+    State(state): State<Arc<AppState>>,
+    Json(batch): Json<mint::BatchMintRequest>,
+) -> Result<Json<mint::BatchMintResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let items = state.dead_letters.as_ref().map(|_| batch.items.clone());
+    let response = mint::mint_batch(
+        &state.rpc,
+        &state.payer,
+        state.author.as_ref(),
+        batch,
+        &state.mint_config,
+        &state.sequences,
+        &state.blockhashes,
+    )
+    .await
+    .map_err(mint_error)?;
+
+    for result in &response.results {
+        match &result.outcome {
+            mint::BatchOutcome::Success(minted) => record_success(&state, minted).await,
+            // Rejected duplicates would only be rejected again.
+            mint::BatchOutcome::Failed { code, .. } if *code == mint::FailureCode::Duplicate => {}
+            mint::BatchOutcome::Failed {
+                code,
+                message,
+                pending_signature,
+            } => {
+                if let (Some(log), Some(items)) = (&state.dead_letters, &items) {
+                    let error = mint::MintError {
+                        code: *code,
+                        message: message.clone(),
+                        logs: Vec::new(),
+                        pending_signature: pending_signature.clone(),
+                    };
+                    let (log, item) = (log.clone(), items[result.index].clone());
+                    if let Err(e) = blocking(move || log.record(item, &error)).await {
+                        tracing::error!("Failed to record dead letter: {}", e);
+                    }
+                }
+            }
+        }
+    }
+    Ok(Json(response))
+}
+
+async fn mint_batch_estimate_handler(
+    State(state): State<Arc<AppState>>,
+    Json(batch): Json<mint::BatchMintRequest>,
+) -> Result<Json<mint::BatchEstimateResponse>, (StatusCode, Json<ErrorResponse>)> {
+    mint::estimate_batch(
+        &state.rpc,
+        &state.payer.pubkey(),
+        state.author.as_ref().map(|author| author.pubkey()).as_ref(),
+        &batch,
+        &state.mint_config,
+    )
+    .await
+    .map(Json)
+    .map_err(mint_error)
+}
+
+async fn instruction_handler(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<mint::InstructionRequest>,
+) -> Result<Json<mint::InstructionResponse>, (StatusCode, Json<ErrorResponse>)> {
+    mint::unsigned_instructions(&req, &state.mint_config)
+        .map(Json)
+        .map_err(mint_error)
+}
+
+async fn decode_handler(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<decode::DecodeRequest>,
+) -> Result<Json<decode::DecodeResponse>, (StatusCode, Json<ErrorResponse>)> {
+    decode::decode_transaction(&req, &state.mint_config)
+        .map(Json)
+        .map_err(|error| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(error))))
+}
+
+async fn ledger_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ledger::LedgerQuery>,
+) -> Result<Json<ledger::LedgerPage>, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .ledger
+        .page(&query)
+        .map(Json)
+        .map_err(|error| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(error))))
+}
+
+async fn manifest_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<manifest::SignedManifest>, (StatusCode, Json<ErrorResponse>)> {
+    manifest::sign_manifest(&state.payer, &state.ledger.recent(usize::MAX))
+        .map(Json)
+        .map_err(|error| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(error)),
+            )
+        })
+}
+
+async fn balance_handler(
+    State(state): State<Arc<AppState>>,
+    Path(pubkey): Path<String>,
+) -> Result<Json<BalanceResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let address = solana_pubkey::Pubkey::from_str(&pubkey).map_err(|e| {
+        (
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse { error: e }),
-        )),
+            Json(ErrorResponse::new(format!(
+                "Invalid pubkey {:?}: {}",
+                pubkey, e
+            ))),
+        )
+    })?;
+    let lamports = state
+        .query_rpc
+        .as_ref()
+        .unwrap_or(&state.rpc)
+        .get_balance(&address)
+        .await
+        .map_err(rpc_error)?;
+
+    Ok(Json(BalanceResponse {
+        pubkey: address.to_string(),
+        lamports,
+        sol: lamports as f64 / LAMPORTS_PER_SOL,
+    }))
+}
+
+/// Top up the payer, or `pubkey`, from the cluster faucet. Refused on
+/// mainnet, which has no faucet.
+async fn airdrop_handler(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AirdropRequest>,
+) -> Result<Json<AirdropResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if state.mint_config.cluster == explorer::Cluster::Mainnet {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse::new(
+                "Airdrops are not available on mainnet".into(),
+            )),
+        ));
+    }
+    let bad_request = |e: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(e)));
+    if req.lamports == 0 {
+        return Err(bad_request("lamports must be positive".into()));
+    }
+    let address = match &req.pubkey {
+        Some(pubkey) => solana_pubkey::Pubkey::from_str(pubkey)
+            .map_err(|e| bad_request(format!("Invalid pubkey {:?}: {}", pubkey, e)))?,
+        None => state.payer.pubkey(),
+    };
+
+    let sent = state
+        .rpc
+        .request_airdrop(&address, req.lamports)
+        .await
+        .map_err(rpc_error)?;
+    Ok(Json(AirdropResponse {
+        signature: sent.signature,
+        pubkey: address.to_string(),
+        lamports: req.lamports,
+        confirmation_level: sent
+            .commitment
+            .map_or("submitted", |level| level.as_str())
+            .to_string(),
+    }))
+}
+
+async fn mint_token_handler(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<spl::TokenMintRequest>,
+) -> Result<Json<spl::TokenMintResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let recipient = solana_pubkey::Pubkey::from_str(&req.recipient).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new(format!(
+                "Invalid recipient {:?}: {}",
+                req.recipient, e
+            ))),
+        )
+    })?;
+    spl::mint_token(
+        &state.rpc,
+        &state.payer,
+        &recipient,
+        &req,
+        &state.mint_config.cluster,
+        &state.blockhashes,
+    )
+    .await
+    .map(Json)
+    .map_err(rpc_error)
+}
+
+/// Every record the payer has minted, newest first.
+async fn all_records_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<records::RecordsQuery>,
+) -> Result<Json<records::RecordsPage>, (StatusCode, Json<ErrorResponse>)> {
+    records::records_since(
+        state.query_rpc.as_ref().unwrap_or(&state.rpc),
+        &state.payer.pubkey(),
+        None,
+        &query,
+        1000,
+        &state.mint_config,
+    )
+    .await
+    .map(Json)
+    .map_err(rpc_error)
+}
+
+/// Records from the SQLite index, most recently minted first.
+async fn indexed_records_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<index::IndexQuery>,
+) -> Result<Json<index::IndexPage>, (StatusCode, Json<ErrorResponse>)> {
+    let Some(index) = state.index.clone() else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse::new("Record index is not configured".into())),
+        ));
+    };
+    blocking(move || index.query_records(&query))
+        .await
+        .map(|records| Json(index::IndexPage { records }))
+        .map_err(|error| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(error)),
+            )
+        })
+}
+
+async fn records_handler(
+    State(state): State<Arc<AppState>>,
+    Path(location_id): Path<String>,
+    Query(query): Query<records::RecordsQuery>,
+) -> Result<Json<records::RecordsPage>, (StatusCode, Json<ErrorResponse>)> {
+    records::records_since(
+        state.query_rpc.as_ref().unwrap_or(&state.rpc),
+        &state.payer.pubkey(),
+        Some(&location_id),
+        &query,
+        1000,
+        &state.mint_config,
+    )
+    .await
+    .map(Json)
+    .map_err(rpc_error)
+}
+
+/// Whether `location_id` already has a record, so clients can avoid
+/// minting it twice.
+async fn exists_handler(
+    State(state): State<Arc<AppState>>,
+    Path(location_id): Path<String>,
+    Query(query): Query<ExistsQuery>,
+) -> Result<Json<ExistsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let mut latest_signature = state.ledger.latest_signature(&location_id);
+    if latest_signature.is_none() && query.scan_chain {
+        let page = records::records_since(
+            state.query_rpc.as_ref().unwrap_or(&state.rpc),
+            &state.payer.pubkey(),
+            Some(&location_id),
+            &records::RecordsQuery {
+                limit: Some(1),
+                ..Default::default()
+            },
+            1000,
+            &state.mint_config,
+        )
+        .await
+        .map_err(rpc_error)?;
+        latest_signature = page.records.into_iter().next().map(|r| r.signature);
+    }
+    Ok(Json(ExistsResponse {
+        exists: latest_signature.is_some(),
+        latest_signature,
+    }))
+}
+
+/// A found record is immutable, so proxies may keep it for
+/// `record_max_age`. Errors are not cached: the transaction may land later.
+async fn record_handler(
+    State(state): State<Arc<AppState>>,
+    Path(signature): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, Json<ErrorResponse>)> {
+    let cache_control = match state.record_max_age {
+        0 => HeaderValue::from_static("no-cache"),
+        max_age => HeaderValue::from_str(&format!("public, max-age={}, immutable", max_age))
+            .expect("digits are a valid header value"),
+    };
+    records::record_by_signature(
+        state.query_rpc.as_ref().unwrap_or(&state.rpc),
+        &signature,
+        &state.mint_config,
+    )
+    .await
+    .map(|record| ([(header::CACHE_CONTROL, cache_control)], Json(record)))
+    .map_err(|e| match e {
+        records::RecordLookupError::NotFound(error) => {
+            (StatusCode::NOT_FOUND, Json(ErrorResponse::new(error)))
+        }
+        records::RecordLookupError::NoRecord(error) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse::new(error)),
+        ),
+        records::RecordLookupError::Rpc(error) => rpc_error(error),
+    })
+}
+
+/// Response shapes a client can pin with `Accept-Version`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ApiVersion {
+    V1,
+}
+
+impl ApiVersion {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "v1" | "1" => Some(Self::V1),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::V1 => "v1",
+        }
+    }
+}
+
+/// Resolve `Accept-Version` (default: latest) into an `ApiVersion` request
+/// extension for handlers to shape responses by, and echo it back. Unknown
+/// versions are rejected with 406.
+async fn api_version(mut req: Request, next: Next) -> Response {
+    let version = match req.headers().get("accept-version") {
+        None => Some(ApiVersion::V1),
+        Some(value) => value.to_str().ok().and_then(ApiVersion::parse),
+    };
+    let Some(version) = version else {
+        let error = format!(
+            "Unsupported Accept-Version {:?}; supported: v1",
+            req.headers()["accept-version"]
+        );
+        return (StatusCode::NOT_ACCEPTABLE, Json(ErrorResponse::new(error))).into_response();
+    };
+
+    req.extensions_mut().insert(version);
+    let mut response = next.run(req).await;
+    response.headers_mut().insert(
+        "content-version",
+        HeaderValue::from_static(version.as_str()),
+    );
+    response
+}
+
+#[derive(serde::Deserialize)]
+struct PrettyQuery {
+    pretty: Option<bool>,
+}
+
+/// Re-serialize JSON responses with indentation when `?pretty=true` is
+/// given (or `PRETTY_JSON` is set and the request does not pass
+/// `pretty=false`). Only affects HTTP output, never memo contents.
+async fn pretty_json(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Response {
+    let pretty = Query::<PrettyQuery>::try_from_uri(req.uri())
+        .ok()
+        .and_then(|query| query.pretty)
+        .unwrap_or(state.pretty_json);
+    let response = next.run(req).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    if !pretty || !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, usize::MAX).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    // Transcoded rather than parsed into a `Value`, which would sort keys.
+    let mut body = Vec::with_capacity(bytes.len() * 2);
+    let transcoded = serde_transcode::transcode(
+        &mut serde_json::Deserializer::from_slice(&bytes),
+        &mut serde_json::Serializer::pretty(&mut body),
+    );
+    if transcoded.is_err() {
+        body = bytes.to_vec();
+    }
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, axum::body::Body::from(body))
+}
+
+fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/readyz", get(readyz))
+        .route("/metrics", get(metrics_handler))
+        .route("/config", get(config_handler))
+        .route("/mint", post(mint_handler))
+        .route("/mint/batch", post(mint_batch_handler))
+        .route("/mint/token", post(mint_token_handler))
+        .route("/mint/batch/estimate", post(mint_batch_estimate_handler))
+        .route("/dead-letter/replay", post(dead_letter_replay_handler))
+        .route("/instruction", post(instruction_handler))
+        .route("/decode", post(decode_handler))
+        .route("/ledger", get(ledger_handler))
+        .route("/manifest", get(manifest_handler))
+        .route("/balance/{pubkey}", get(balance_handler))
+        .route("/airdrop", post(airdrop_handler))
+        .route("/records", get(all_records_handler))
+        .route("/records/{location_id}", get(records_handler))
+        .route("/index/records", get(indexed_records_handler))
+        .route("/record/{signature}", get(record_handler))
+        .route("/exists/{location_id}", get(exists_handler))
+        .layer(middleware::from_fn_with_state(state.clone(), pretty_json))
+        .layer(middleware::from_fn(api_version))
+        .layer(CorsLayer::permissive())
+        .with_state(state)
+}
+
+/// `check-wallet <path>`: load the wallet, print its public key and return
+/// the process exit code, without touching the network.
+fn check_wallet(path: &std::path::Path) -> i32 {
+    match wallet::load_wallet(path) {
+        Ok(keypair) => {
+            println!("{}", keypair.pubkey());
+            if let Some(warning) = wallet::permission_warning(path) {
+                eprintln!("Warning: {}", warning);
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
     }
 }
 
 #[tokio::main]
 async fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("check-wallet") {
+        let Some(path) = args.get(2) else {
+            eprintln!("Usage: server check-wallet <path>");
+            std::process::exit(2);
+        };
+        std::process::exit(check_wallet(std::path::Path::new(path)));
+    }
+
     dotenvy::dotenv().ok();
     tracing_subscriber::fmt::init();
+    telemetry::install();
 
     let rpc_url =
         std::env::var("SOLANA_RPC_URL").unwrap_or_else(|_| "https://api.devnet.solana.com".into());
@@ -69,24 +981,1149 @@ async fn main() {
         .map(PathBuf::from)
         .unwrap_or_else(|_| PathBuf::from("devnet-wallet.json"));
 
-    let payer = wallet::load_wallet(&wallet_path).expect("Failed to load wallet");
+    let mint_config = mint::MintConfig::from_env().expect("Invalid mint configuration");
+
+    let payer = if config::env_or("WALLET_FROM_STDIN", false) {
+        wallet::load_from_reader(std::io::stdin().lock()).expect("Failed to read wallet from stdin")
+    } else {
+        if let Some(warning) = wallet::permission_warning(&wallet_path) {
+            tracing::warn!("{}", warning);
+        }
+        wallet::load_wallet(&wallet_path).expect("Failed to load wallet")
+    };
     tracing::info!("Wallet loaded: {}", payer.pubkey());
+    let author = std::env::var("AUTHOR_WALLET_PATH")
+        .ok()
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .map(|path| {
+            if let Some(warning) = wallet::permission_warning(&path) {
+                tracing::warn!("{}", warning);
+            }
+            wallet::load_wallet(&path).expect("Failed to load author wallet")
+        });
+    if let Some(author) = &author {
+        tracing::info!("Records authored by {}", author.pubkey());
+    }
+
+    let proxy = std::env::var("RPC_PROXY")
+        .or_else(|_| std::env::var("HTTP_PROXY"))
+        .ok();
+    if proxy.is_some() {
+        tracing::info!("Routing RPC traffic through proxy");
+    }
+    let endpoints = std::env::var("RPC_ENDPOINTS")
+        .ok()
+        .map(|json| rpc::parse_endpoint_configs(&json).expect("Invalid RPC_ENDPOINTS"))
+        .unwrap_or_default();
+    let inflight_limit = match config::env_or("RPC_MAX_INFLIGHT", 0) {
+        0 => None,
+        limit => Some(Arc::new(tokio::sync::Semaphore::new(limit))),
+    };
+    let commitment = config::env_parse("RPC_COMMITMENT", rpc::CommitmentLevel::Confirmed)
+        .expect("Invalid RPC_COMMITMENT");
+    let blockhash_commitment =
+        config::env_parse("BLOCKHASH_COMMITMENT", rpc::CommitmentLevel::Confirmed)
+            .expect("Invalid BLOCKHASH_COMMITMENT");
+    let airdrop_commitment =
+        config::env_parse("AIRDROP_COMMITMENT", rpc::CommitmentLevel::Finalized)
+            .expect("Invalid AIRDROP_COMMITMENT");
+    let build_rpc = |url: &str| {
+        let rpc = rpc::RpcClient::new(url)
+            .with_commitment(commitment)
+            .with_blockhash_commitment(blockhash_commitment)
+            .with_confirm_parse_retries(config::env_or("CONFIRM_PARSE_RETRIES", 3))
+            .with_blockhash_expiry_check(config::env_or("CHECK_BLOCKHASH_EXPIRY", true))
+            .with_send_max_retries(
+                std::env::var("SEND_MAX_RETRIES")
+                    .ok()
+                    .and_then(|v| v.parse().ok()),
+            )
+            .with_request_timeout(Duration::from_millis(config::env_or(
+                "RPC_TIMEOUT_MS",
+                30_000,
+            )))
+            .with_request_retries(config::env_or("RPC_RETRIES", 2), rpc::RETRY_BACKOFF)
+            .with_poll_timeout(Duration::from_millis(config::env_or(
+                "POLL_TIMEOUT_MS",
+                2000,
+            )))
+            .with_confirm_timeout(Duration::from_secs(config::env_or(
+                "CONFIRM_TIMEOUT_SECS",
+                rpc::CONFIRM_TIMEOUT.as_secs(),
+            )))
+            .with_fast_poll(
+                Duration::from_millis(config::env_or("FAST_POLL_MS", 100)),
+                Duration::from_millis(config::env_or("FAST_POLL_WINDOW_MS", 1000)),
+            )
+            .with_confirm_config(rpc::ConfirmConfig {
+                max_polls: std::env::var("CONFIRM_MAX_POLLS")
+                    .ok()
+                    .and_then(|v| v.parse().ok()),
+                interval: Duration::from_millis(config::env_or("CONFIRM_POLL_INTERVAL_MS", 500)),
+                max_interval: Duration::from_millis(config::env_or(
+                    "CONFIRM_MAX_POLL_INTERVAL_MS",
+                    4000,
+                )),
+            })
+            .with_airdrop_confirmation(
+                airdrop_commitment,
+                Duration::from_secs(config::env_or("AIRDROP_TIMEOUT_SECS", 60)),
+            );
+        let rpc = match endpoints.iter().find(|endpoint| endpoint.url == url) {
+            Some(endpoint) => rpc.with_endpoint_config(endpoint),
+            None => rpc,
+        };
+        // Shared, so the primary and query endpoints are capped together.
+        let rpc = match &inflight_limit {
+            Some(limit) => rpc.with_inflight_limit(limit.clone()),
+            None => rpc,
+        };
+        let rpc = rpc
+            .with_chaos_fail_rate(config::env_or("CHAOS_FAIL_RATE", 0.0))
+            .expect("Invalid CHAOS_FAIL_RATE");
+        match &proxy {
+            Some(proxy) => rpc.with_proxy(proxy).expect("Invalid RPC proxy"),
+            None => rpc,
+        }
+    };
+    let rpc = build_rpc(&rpc_url);
+    let ws_url = match std::env::var("SOLANA_WS_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+    {
+        Some(url) => Some(url),
+        None if config::env_or("CONFIRM_VIA_WS", false) => {
+            Some(rpc::ws_url_from_http(&rpc_url).expect("Invalid SOLANA_RPC_URL"))
+        }
+        None => None,
+    };
+    let rpc = match &ws_url {
+        Some(ws_url) => rpc.with_ws_url(ws_url),
+        None => rpc,
+    };
+    let query_rpc = std::env::var("QUERY_RPC_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .map(|url| build_rpc(&url));
+
+    if config::env_or("RPC_WARMUP", false) {
+        warm_up(&rpc, WARMUP_TIMEOUT).await;
+        if let Some(query_rpc) = &query_rpc {
+            warm_up(query_rpc, WARMUP_TIMEOUT).await;
+        }
+    }
+
+    let cluster = if config::env_or("DETECT_CLUSTER", true) {
+        match rpc.get_genesis_hash().await {
+            Ok(hash) => explorer::Cluster::from_genesis_hash(&hash, &rpc_url),
+            Err(e) => {
+                tracing::warn!("Cluster detection failed, guessing from RPC URL: {}", e);
+                explorer::Cluster::from_rpc_url(&rpc_url)
+            }
+        }
+    } else {
+        explorer::Cluster::from_rpc_url(&rpc_url)
+    };
+    tracing::info!("Cluster: {:?}", cluster);
+    if cluster == explorer::Cluster::Mainnet && rpc.injects_chaos() {
+        panic!("Refusing to inject failures against mainnet; unset CHAOS_FAIL_RATE");
+    }
+
+    let startup_airdrop: u64 = config::env_or("STARTUP_AIRDROP_LAMPORTS", 0);
+    if startup_airdrop > 0 && cluster == explorer::Cluster::Mainnet {
+        tracing::warn!("Ignoring STARTUP_AIRDROP_LAMPORTS on mainnet");
+    } else if startup_airdrop > 0 && matches!(rpc.get_balance(&payer.pubkey()).await, Ok(0)) {
+        match rpc.request_airdrop(&payer.pubkey(), startup_airdrop).await {
+            Ok(sent) => tracing::info!("Funded empty wallet via airdrop {}", sent.signature),
+            Err(e) => tracing::warn!("Startup airdrop failed: {}", e),
+        }
+    }
+
+    let index = std::env::var("INDEX_DB_PATH")
+        .ok()
+        .filter(|path| !path.is_empty())
+        .map(|path| Arc::new(index::RecordIndex::open(&path).expect("Invalid INDEX_DB_PATH")));
 
     let state = Arc::new(AppState {
+        rpc,
+        query_rpc,
         rpc_url: rpc_url.clone(),
         payer,
+        author,
+        mint_config: mint::MintConfig {
+            cluster,
+            ..mint_config
+        },
+        sequences: sequence::SequenceTracker::default(),
+        ledger: ledger::Ledger::default(),
+        blockhashes: blockhash_cache::BlockhashCache::new(Duration::from_secs(config::env_or(
+            "BLOCKHASH_TTL_SECS",
+            blockhash_cache::DEFAULT_BLOCKHASH_TTL.as_secs(),
+        ))),
+        dead_letters: std::env::var("DEAD_LETTER_PATH")
+            .ok()
+            .filter(|path| !path.is_empty())
+            .map(dead_letter::DeadLetterLog::new),
+        index,
+        idempotency: idempotency::IdempotencyCache::new(
+            Duration::from_secs(config::env_or(
+                "IDEMPOTENCY_TTL_SECS",
+                idempotency::DEFAULT_IDEMPOTENCY_TTL.as_secs(),
+            )),
+            config::env_or(
+                "IDEMPOTENCY_MAX_KEYS",
+                idempotency::DEFAULT_IDEMPOTENCY_MAX_KEYS,
+            ),
+        ),
+        pretty_json: config::env_or("PRETTY_JSON", false),
+        record_max_age: config::env_or("RECORD_CACHE_MAX_AGE_SECS", DEFAULT_RECORD_MAX_AGE_SECS),
     });
 
-    let app = Router::new()
-        .route("/health", get(health))
-        .route("/mint", post(mint_handler))
-        .layer(CorsLayer::permissive())
-        .with_state(state);
+    let verify_interval: u64 = config::env_or("VERIFY_INTERVAL_SECS", 0);
+    if verify_interval > 0 {
+        let state = state.clone();
+        let sample_size = config::env_or("VERIFY_SAMPLE_SIZE", 10);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(verify_interval));
+            loop {
+                ticker.tick().await;
+                let entries = state.ledger.recent(sample_size);
+                let report = verify::verify_entries(&state.rpc, &entries, &state.mint_config).await;
+                tracing::info!(
+                    "Re-verified {} records: {} mismatched, {} unreadable",
+                    report.checked,
+                    report.mismatches.len(),
+                    report.errors.len()
+                );
+            }
+        });
+    }
+
+    let app = router(state);
 
     let port = std::env::var("PORT").unwrap_or_else(|_| "3001".into());
     let addr = format!("0.0.0.0:{}", port);
     tracing::info!("Solana service listening on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal())
+        .await
+        .unwrap();
+    tracing::info!("Shutdown complete");
+}
+
+/// Resolve on SIGINT (Ctrl-C) or SIGTERM (as sent by Kubernetes on
+/// rollout). The server then stops accepting connections but lets
+/// in-flight requests, including pending mints, run to completion.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to listen for Ctrl-C");
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    tracing::info!("Shutdown signal received; finishing in-flight requests");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mock_rpc::MockRpc;
+    use serde_json::{json, Value};
+
+    fn test_state(mock: &MockRpc) -> AppState {
+        AppState {
+            rpc: rpc::RpcClient::new(&mock.url),
+            query_rpc: None,
+            rpc_url: mock.url.clone(),
+            payer: Keypair::new(),
+            author: None,
+            mint_config: mint::MintConfig::default(),
+            sequences: sequence::SequenceTracker::default(),
+            ledger: ledger::Ledger::default(),
+            blockhashes: blockhash_cache::BlockhashCache::default(),
+            dead_letters: None,
+            index: None,
+            idempotency: idempotency::IdempotencyCache::default(),
+            pretty_json: false,
+            record_max_age: DEFAULT_RECORD_MAX_AGE_SECS,
+        }
+    }
+
+    /// Serve the full app against `mock` and return its base URL.
+    async fn spawn_app(mock: &MockRpc) -> String {
+        serve(test_state(mock)).await
+    }
+
+    async fn serve(state: AppState) -> String {
+        let state = Arc::new(state);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, router(state)).await.unwrap();
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_mint_end_to_end() {
+        let mock = MockRpc::start().await;
+        let app = spawn_app(&mock).await;
+
+        let resp = reqwest::Client::new()
+            .post(format!("{}/mint", app))
+            .json(&json!({"location_id": "iceland-reykjavik", "capacity_mw": 50.0}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body["memo_content"]["location_id"], "iceland-reykjavik");
+        assert_eq!(body["confirmation_level"], "confirmed");
+        assert_eq!(mock.calls("sendTransaction"), 1);
+        assert_eq!(
+            body["signature"],
+            mock.sent_transactions()[0].signatures[0].to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_minted_records_served_from_index() {
+        let mock = MockRpc::start().await;
+        let client = reqwest::Client::new();
+        let app = spawn_app(&mock).await;
+        let resp = client
+            .get(format!("{}/index/records", app))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 404);
+
+        let mut state = test_state(&mock);
+        state.index = Some(Arc::new(index::RecordIndex::in_memory()));
+        let app = serve(state).await;
+        for location_id in ["iceland-reykjavik", "norway-oslo"] {
+            let resp = client
+                .post(format!("{}/mint", app))
+                .json(&json!({"location_id": location_id, "grade": "A"}))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), 200);
+        }
+
+        let body: Value = client
+            .get(format!("{}/index/records?location_id=norway-oslo", app))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let records = body["records"].as_array().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["location_id"], "norway-oslo");
+        assert_eq!(records[0]["feasibility_grade"], "A");
+    }
+
+    #[tokio::test]
+    async fn test_exists_reports_minted_locations() {
+        let mock = MockRpc::start().await;
+        let app = spawn_app(&mock).await;
+        let client = reqwest::Client::new();
+
+        let minted: Value = client
+            .post(format!("{}/mint", app))
+            .json(&json!({"location_id": "iceland-reykjavik"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+
+        let exists = |location_id: &'static str| {
+            let client = client.clone();
+            let url = format!("{}/exists/{}", app, location_id);
+            async move {
+                client
+                    .get(url)
+                    .send()
+                    .await
+                    .unwrap()
+                    .json::<Value>()
+                    .await
+                    .unwrap()
+            }
+        };
+        let known = exists("iceland-reykjavik").await;
+        assert_eq!(known["exists"], true);
+        assert_eq!(known["latest_signature"], minted["signature"]);
+
+        let unknown = exists("norway-oslo").await;
+        assert_eq!(unknown["exists"], false);
+        assert!(unknown["latest_signature"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_mint_reports_injected_rpc_failure() {
+        let mock = MockRpc::start().await;
+        mock.fail_http("sendTransaction", 503);
+        let app = spawn_app(&mock).await;
+
+        let resp = reqwest::Client::new()
+            .post(format!("{}/mint", app))
+            .json(&json!({"location_id": "iceland-reykjavik"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 502);
+
+        let body: Value = resp.json().await.unwrap();
+        assert!(body["error"].as_str().unwrap().contains("Send tx"));
+        assert_eq!(body["error_retryable"], true);
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_mints_once() {
+        let mock = MockRpc::start().await;
+        let app = spawn_app(&mock).await;
+        let client = reqwest::Client::new();
+        let mint = || {
+            client
+                .post(format!("{}/mint", app))
+                .header("Idempotency-Key", "retry-1")
+                .json(&json!({"location_id": "iceland-reykjavik"}))
+                .send()
+        };
+
+        let first: Value = mint().await.unwrap().json().await.unwrap();
+        let second: Value = mint().await.unwrap().json().await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(mock.calls("sendTransaction"), 1);
+
+        let reused = client
+            .post(format!("{}/mint", app))
+            .header("Idempotency-Key", "retry-1")
+            .json(&json!({"location_id": "norway-oslo"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(reused.status(), 422);
+        assert_eq!(mock.calls("sendTransaction"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_status_poll_leaves_mint_pending() {
+        let mock = MockRpc::start().await;
+        mock.disconnect("getSignatureStatuses");
+        mock.set_default("getSignatureStatuses", json!({"value": [null]}));
+        let app = serve(AppState {
+            rpc: rpc::RpcClient::new(&mock.url).with_confirm_config(rpc::ConfirmConfig {
+                max_polls: Some(2),
+                ..Default::default()
+            }),
+            ..test_state(&mock)
+        })
+        .await;
+        let client = reqwest::Client::new();
+        let mint = || {
+            client
+                .post(format!("{}/mint", app))
+                .header("Idempotency-Key", "poll-fails")
+                .json(&json!({"location_id": "iceland-reykjavik"}))
+                .send()
+        };
+
+        let signature = || mock.sent_transactions()[0].signatures[0].to_string();
+        let first: Value = mint().await.unwrap().json().await.unwrap();
+        assert_eq!(first["pending_signature"], signature(), "{}", first);
+        let retry = mint().await.unwrap();
+        assert_eq!(retry.status(), 409);
+        let retry: Value = retry.json().await.unwrap();
+        assert_eq!(retry["pending_signature"], signature());
+        assert_eq!(mock.calls("sendTransaction"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_sends_one_request_and_tolerates_failure() {
+        let mock = MockRpc::start().await;
+        let rpc = rpc::RpcClient::new(&mock.url);
+        warm_up(&rpc, WARMUP_TIMEOUT).await;
+        assert_eq!(mock.calls("getHealth"), 1);
+
+        mock.fail_http("getHealth", 503);
+        warm_up(&rpc, WARMUP_TIMEOUT).await;
+        assert_eq!(mock.calls("getHealth"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_same_location_mints_get_consecutive_seq() {
+        let mock = MockRpc::start().await;
+        let confirmed = json!({
+            "context": {"slot": 1},
+            "value": [{"slot": 1, "confirmations": 0, "err": null, "confirmationStatus": "confirmed"}]
+        });
+        // Keep the first mint in flight while the second arrives.
+        mock.respond_slow(
+            "getSignatureStatuses",
+            Duration::from_millis(200),
+            confirmed,
+        );
+        let app = spawn_app(&mock).await;
+        let client = reqwest::Client::new();
+        let mint = || async {
+            client
+                .post(format!("{}/mint", app))
+                .json(&json!({"location_id": "iceland-reykjavik"}))
+                .send()
+                .await
+                .unwrap()
+                .json::<Value>()
+                .await
+                .unwrap()
+        };
+
+        let (first, second) = tokio::join!(mint(), mint());
+        let mut seqs = [
+            first["memo_content"]["seq"].as_u64(),
+            second["memo_content"]["seq"].as_u64(),
+        ];
+        seqs.sort();
+        assert_eq!(seqs, [Some(1), Some(2)], "{} {}", first, second);
+    }
+
+    #[tokio::test]
+    async fn test_airdrop_refused_on_mainnet() {
+        let mock = MockRpc::start().await;
+        // Airdrops wait for finalization by default.
+        mock.set_default(
+            "getSignatureStatuses",
+            json!({
+                "context": {"slot": 1},
+                "value": [{"slot": 1, "confirmations": null, "err": null, "confirmationStatus": "finalized"}]
+            }),
+        );
+        let client = reqwest::Client::new();
+        let payer = Keypair::new();
+        let payer_pubkey = payer.pubkey().to_string();
+        let devnet = serve(AppState {
+            payer,
+            ..test_state(&mock)
+        })
+        .await;
+
+        let resp = client
+            .post(format!("{}/airdrop", devnet))
+            .json(&json!({"lamports": 1_000_000_000u64}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body["signature"], "airdrop-signature");
+        assert_eq!(body["pubkey"], payer_pubkey);
+        assert_eq!(body["confirmation_level"], "finalized");
+        assert_eq!(mock.calls("requestAirdrop"), 1);
+
+        let mainnet = serve(AppState {
+            mint_config: mint::MintConfig {
+                cluster: explorer::Cluster::Mainnet,
+                ..Default::default()
+            },
+            ..test_state(&mock)
+        })
+        .await;
+        let resp = client
+            .post(format!("{}/airdrop", mainnet))
+            .json(&json!({"lamports": 1_000_000_000u64}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 403);
+        assert_eq!(mock.calls("requestAirdrop"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_validation_error_not_retryable() {
+        let mock = MockRpc::start().await;
+        let app = spawn_app(&mock).await;
+
+        let resp = reqwest::Client::new()
+            .post(format!("{}/mint", app))
+            .json(&json!({"location_id": "iceland-reykjavik", "lat": 91.0, "lon": 0.0}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 400);
+
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body["error_retryable"], false, "{}", body);
+    }
+
+    #[tokio::test]
+    async fn test_failed_mint_written_to_dead_letter_log() {
+        let mock = MockRpc::start().await;
+        mock.fail_http("sendTransaction", 503);
+        let path = std::env::temp_dir().join(format!("dead-letters-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let app = serve(AppState {
+            dead_letters: Some(dead_letter::DeadLetterLog::new(&path)),
+            ..test_state(&mock)
+        })
+        .await;
+
+        let client = reqwest::Client::new();
+        let resp = client
+            .post(format!("{}/mint", app))
+            .json(&json!({"location_id": "iceland-reykjavik", "capacity_mw": 50.0}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 502);
+
+        let lines: Vec<Value> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["request"]["location_id"], "iceland-reykjavik");
+        assert_eq!(lines[0]["code"], "network");
+        assert!(lines[0]["error"].as_str().unwrap().contains("Send tx"));
+        assert!(lines[0]["failed_at"].is_string());
+
+        let replay = |index: usize| {
+            client
+                .post(format!("{}/dead-letter/replay", app))
+                .json(&json!({"index": index}))
+                .send()
+        };
+        let resp = replay(0).await.unwrap();
+        assert_eq!(resp.status(), 200);
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body["memo_content"]["capacity_mw"], 50.0);
+        assert_eq!(replay(0).await.unwrap().status(), 409);
+        assert_eq!(mock.calls("sendTransaction"), 2);
+
+        // A timed-out entry whose transaction landed is not minted again.
+        let timed_out = json!({
+            "request": {"location_id": "norway-oslo"},
+            "code": "timeout",
+            "error": "Transaction confirmation timeout",
+            "failed_at": "2026-01-01T00:00:00+00:00",
+            "pending_signature": "landed-sig"
+        });
+        let mut log = std::fs::read_to_string(&path).unwrap();
+        log.push_str(&format!("{}\n", timed_out));
+        std::fs::write(&path, log).unwrap();
+        mock.respond(
+            "getTransaction",
+            json!({"slot": 1, "meta": {"err": null}, "transaction": {}}),
+        );
+        let resp = replay(1).await.unwrap();
+        let entries = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(resp.status(), 409);
+        assert_eq!(mock.calls("sendTransaction"), 2);
+        assert!(entries
+            .lines()
+            .nth(1)
+            .unwrap()
+            .contains(r#""replayed_signature":"landed-sig""#));
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_batch_item_not_minted_again_on_replay() {
+        let mock = MockRpc::start().await;
+        mock.set_default("getSignatureStatuses", json!({"value": [null]}));
+        let path =
+            std::env::temp_dir().join(format!("batch-dead-letters-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let app = serve(AppState {
+            rpc: rpc::RpcClient::new(&mock.url).with_confirm_config(rpc::ConfirmConfig {
+                max_polls: Some(1),
+                ..Default::default()
+            }),
+            dead_letters: Some(dead_letter::DeadLetterLog::new(&path)),
+            ..test_state(&mock)
+        })
+        .await;
+
+        let client = reqwest::Client::new();
+        let body: Value = client
+            .post(format!("{}/mint/batch", app))
+            .json(&json!({"items": [{"location_id": "iceland-reykjavik"}]}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let signature = mock.sent_transactions()[0].signatures[0].to_string();
+        assert_eq!(body["results"][0]["code"], "timeout");
+        assert_eq!(body["results"][0]["pending_signature"], signature);
+
+        mock.respond(
+            "getTransaction",
+            json!({"slot": 1, "meta": {"err": null}, "transaction": {}}),
+        );
+        let resp = client
+            .post(format!("{}/dead-letter/replay", app))
+            .json(&json!({"index": 0}))
+            .send()
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(resp.status(), 409);
+        assert_eq!(mock.calls("sendTransaction"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_balance_of_arbitrary_pubkey() {
+        let mock = MockRpc::start().await;
+        mock.respond(
+            "getBalance",
+            json!({"context": {"slot": 1}, "value": 1_500_000_000u64}),
+        );
+        mock.fail_http("getBalance", 503);
+        let app = spawn_app(&mock).await;
+        let pubkey = solana_pubkey::Pubkey::new_unique();
+
+        let resp = reqwest::get(format!("{}/balance/{}", app, pubkey))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body["pubkey"], pubkey.to_string());
+        assert_eq!(body["lamports"], 1_500_000_000u64);
+        assert_eq!(body["sol"], 1.5);
+        let params = mock.last_params("getBalance").unwrap();
+        assert_eq!(params[0], pubkey.to_string());
+
+        let resp = reqwest::get(format!("{}/balance/{}", app, pubkey))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 502);
+
+        let resp = reqwest::get(format!("{}/balance/not-a-key", app))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 400);
+        let body: Value = resp.json().await.unwrap();
+        assert!(body["error"].as_str().unwrap().contains("Invalid pubkey"));
+    }
+
+    #[tokio::test]
+    async fn test_pretty_query_indents_same_response() {
+        let mock = MockRpc::start().await;
+        let app = spawn_app(&mock).await;
+
+        let compact = reqwest::get(format!("{}/config", app))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        let pretty = reqwest::get(format!("{}/config?pretty=true", app))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+
+        assert!(!compact.contains('\n'));
+        assert!(pretty.starts_with("{\n  \"memo_max_bytes\": 566,\n"));
+        assert_eq!(
+            serde_json::from_str::<Value>(&compact).unwrap(),
+            serde_json::from_str::<Value>(&pretty).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_health_reports_canned_balance() {
+        let mock = MockRpc::start().await;
+        mock.set_default("getBalance", json!({"value": 2_500_000_000u64}));
+        let app = spawn_app(&mock).await;
+
+        let body: Value = reqwest::get(format!("{}/health", app))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(body["balance_sol"], 2.5);
+        assert_eq!(body["tx_count"], 268_435_456);
+    }
+
+    #[tokio::test]
+    async fn test_health_tx_count_null_when_rpc_fails() {
+        let mock = MockRpc::start().await;
+        mock.respond_error("getTransactionCount", "node is behind");
+        let app = spawn_app(&mock).await;
+
+        let body: Value = reqwest::get(format!("{}/health", app))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(body["tx_count"].is_null());
+        assert_eq!(body["status"], "ok");
+    }
+
+    #[tokio::test]
+    async fn test_health_retries_transient_balance_failure() {
+        let mock = MockRpc::start().await;
+        mock.fail_http("getBalance", 503);
+        let app = spawn_app(&mock).await;
+
+        let body: Value = reqwest::get(format!("{}/health", app))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["balance_sol"], 1.0);
+        assert_eq!(mock.calls("getBalance"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_config_reports_memo_limit() {
+        let mock = MockRpc::start().await;
+        let app = spawn_app(&mock).await;
+
+        let body: Value = reqwest::get(format!("{}/config", app))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(body["memo_max_bytes"], mint::MEMO_MAX_BYTES);
+        assert_eq!(body["blockhash_commitment"], "confirmed");
+        assert_eq!(body["supported_encodings"], json!(["json", "gzip+base64"]));
+        assert_eq!(body["version_discriminator"], false);
+
+        let app = serve(AppState {
+            mint_config: mint::MintConfig {
+                memo_base64url: true,
+                version_discriminator: true,
+                ..Default::default()
+            },
+            ..test_state(&mock)
+        })
+        .await;
+        let body: Value = reqwest::get(format!("{}/config", app))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(
+            body["supported_encodings"],
+            json!(["json", "gzip+base64url"])
+        );
+        assert_eq!(body["version_discriminator"], true);
+    }
+
+    #[tokio::test]
+    async fn test_mint_assigns_increasing_seq_and_rejects_out_of_order() {
+        let mock = MockRpc::start().await;
+        let app = spawn_app(&mock).await;
+        let client = reqwest::Client::new();
+        let mint = |body: Value| client.post(format!("{}/mint", app)).json(&body).send();
+
+        for expected in 1..=2 {
+            let body: Value = mint(json!({"location_id": "iceland-reykjavik"}))
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            assert_eq!(body["memo_content"]["seq"], expected);
+        }
+
+        let resp = mint(json!({"location_id": "iceland-reykjavik", "seq": 5}))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 400);
+
+        let resp = mint(json!({"location_id": "iceland-reykjavik", "seq": 3}))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_readyz_reports_unhealthy_query_endpoint() {
+        let primary = MockRpc::start().await;
+        let query = MockRpc::start().await;
+        query.respond_error("getHealth", "Node is behind by 120 slots");
+        let mut state = test_state(&primary);
+        state.query_rpc = Some(rpc::RpcClient::new(&query.url));
+        let app = serve(state).await;
+
+        let resp = reqwest::get(format!("{}/readyz", app)).await.unwrap();
+        assert_eq!(resp.status(), 503);
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body["ready"], false);
+        assert_eq!(body["endpoints"][0]["role"], "primary");
+        assert_eq!(body["endpoints"][0]["healthy"], true);
+        assert_eq!(body["endpoints"][1]["role"], "query");
+        assert_eq!(body["endpoints"][1]["healthy"], false);
+        assert!(body["endpoints"][1]["error"]
+            .as_str()
+            .unwrap()
+            .contains("behind"));
+    }
+
+    /// Value of an unlabelled sample in Prometheus text output.
+    fn sample(metrics: &str, name: &str) -> f64 {
+        metrics
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(' '))
+            .map_or(0.0, |value| value.trim().parse().unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_metrics_records_memo_size_after_mint() {
+        telemetry::install();
+        let mock = MockRpc::start().await;
+        let app = spawn_app(&mock).await;
+        let count = "memo_size_bytes_count";
+        let metrics = || async {
+            reqwest::get(format!("{}/metrics", app))
+                .await
+                .unwrap()
+                .text()
+                .await
+                .unwrap()
+        };
+        let before = sample(&metrics().await, count);
+
+        let resp = reqwest::Client::new()
+            .post(format!("{}/mint", app))
+            .json(&json!({"location_id": "iceland-reykjavik"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+
+        let after = metrics().await;
+        // Other tests mint concurrently into the same global recorder.
+        assert!(sample(&after, count) >= before + 1.0, "{}", after);
+        assert!(
+            after.contains("memo_size_bytes_bucket{le=\"566\"}"),
+            "{}",
+            after
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metrics_count_mints_and_report_balance() {
+        telemetry::install();
+        let mock = MockRpc::start().await;
+        let app = spawn_app(&mock).await;
+        let metrics = || async {
+            reqwest::get(format!("{}/metrics", app))
+                .await
+                .unwrap()
+                .text()
+                .await
+                .unwrap()
+        };
+        let before = metrics().await;
+
+        let client = reqwest::Client::new();
+        let resp = client
+            .post(format!("{}/mint", app))
+            .json(&json!({"location_id": "iceland-reykjavik"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+        reqwest::get(format!("{}/health", app)).await.unwrap();
+
+        let after = metrics().await;
+        // Other tests mint concurrently into the same global recorder.
+        for name in [
+            "mints_attempted_total",
+            "mints_succeeded_total",
+            "confirmation_latency_seconds_count",
+        ] {
+            assert!(
+                sample(&after, name) >= sample(&before, name) + 1.0,
+                "{}: {}",
+                name,
+                after
+            );
+        }
+        assert!(sample(&after, "payer_balance_sol") > 0.0, "{}", after);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_count_each_batch_item() {
+        telemetry::install();
+        let mock = MockRpc::start().await;
+        let app = spawn_app(&mock).await;
+        let client = reqwest::Client::new();
+        let attempts = || async {
+            let metrics = reqwest::get(format!("{}/metrics", app))
+                .await
+                .unwrap()
+                .text()
+                .await
+                .unwrap();
+            sample(&metrics, "mints_attempted_total")
+        };
+        let before = attempts().await;
+
+        let resp = client
+            .post(format!("{}/mint/batch", app))
+            .json(&json!({"items": [
+                {"location_id": "iceland-reykjavik"},
+                {"location_id": "norway-oslo"},
+                {"location_id": "sweden-lulea"}
+            ]}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+        // Other tests mint concurrently into the same global recorder.
+        assert!(attempts().await >= before + 3.0);
+    }
+
+    #[tokio::test]
+    async fn test_last_mint_timestamp_updates_after_mint() {
+        telemetry::install();
+        let mock = MockRpc::start().await;
+        let app = spawn_app(&mock).await;
+        let health = || async {
+            reqwest::get(format!("{}/health", app))
+                .await
+                .unwrap()
+                .json::<Value>()
+                .await
+                .unwrap()
+        };
+        assert!(health().await["last_mint_unix_ts"].is_null());
+
+        let started = chrono::Utc::now().timestamp();
+        let resp = reqwest::Client::new()
+            .post(format!("{}/mint", app))
+            .json(&json!({"location_id": "iceland-reykjavik"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+
+        let last_mint = health().await["last_mint_unix_ts"].as_i64().unwrap();
+        assert!(last_mint >= started);
+        let metrics = reqwest::get(format!("{}/metrics", app))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        // Other tests mint concurrently into the same global recorder.
+        assert!(sample(&metrics, "last_mint_unix_ts") >= started as f64);
+    }
+
+    #[tokio::test]
+    async fn test_found_record_cacheable_and_health_not() {
+        let mock = MockRpc::start().await;
+        let app = spawn_app(&mock).await;
+        let memo = serde_json::to_string(&mint::build_memo(
+            &mint::MintRequest::builder("iceland-reykjavik").build(),
+            &mint::MintConfig::default(),
+        ))
+        .unwrap();
+        mock.respond(
+            "getTransaction",
+            json!({
+                "slot": 42,
+                "blockTime": 1_700_000_042,
+                "transaction": {"message": {"instructions": [{"program": "spl-memo", "parsed": memo}]}}
+            }),
+        );
+
+        let found = reqwest::get(format!("{}/record/sig", app)).await.unwrap();
+        assert_eq!(found.status(), 200);
+        assert_eq!(
+            found.headers()["cache-control"],
+            "public, max-age=86400, immutable"
+        );
+
+        // The mock answers `null` once its queued transaction is used up.
+        let missing = reqwest::get(format!("{}/record/sig", app)).await.unwrap();
+        assert_eq!(missing.status(), 404);
+        assert!(missing.headers().get("cache-control").is_none());
+
+        let health = reqwest::get(format!("{}/health", app)).await.unwrap();
+        assert_eq!(health.headers()["cache-control"], "no-store");
+    }
+
+    #[tokio::test]
+    async fn test_accept_version_v1_served() {
+        let mock = MockRpc::start().await;
+        let app = spawn_app(&mock).await;
+
+        let resp = reqwest::Client::new()
+            .get(format!("{}/config", app))
+            .header("Accept-Version", "v1")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.headers()["content-version"], "v1");
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_accept_version_rejected() {
+        let mock = MockRpc::start().await;
+        let app = spawn_app(&mock).await;
+
+        let resp = reqwest::Client::new()
+            .get(format!("{}/config", app))
+            .header("Accept-Version", "v2")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 406);
+        let body: Value = resp.json().await.unwrap();
+        assert!(body["error"].as_str().unwrap().contains("v2"));
+    }
+
+    #[test]
+    fn test_check_wallet_exit_codes() {
+        let dir = std::env::temp_dir();
+        let valid = dir.join(format!("check-wallet-valid-{}.json", std::process::id()));
+        let invalid = dir.join(format!("check-wallet-invalid-{}.json", std::process::id()));
+        let bytes = Keypair::new().to_bytes().to_vec();
+        std::fs::write(&valid, serde_json::to_string(&bytes).unwrap()).unwrap();
+        std::fs::write(&invalid, "[1, 2, 3]").unwrap();
+
+        let codes = (check_wallet(&valid), check_wallet(&invalid));
+        std::fs::remove_file(&valid).unwrap();
+        std::fs::remove_file(&invalid).unwrap();
+        assert_eq!(codes, (0, 1));
+        assert_eq!(check_wallet(&dir.join("no-such-wallet.json")), 1);
+    }
 }
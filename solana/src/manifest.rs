@@ -0,0 +1,79 @@
+use crate::ledger::LedgerEntry;
+use chrono::Utc;
+use serde::Serialize;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+/// What the payer signs: every ledger entry, in mint order.
+#[derive(Debug, Serialize)]
+struct ManifestBody<'a> {
+    signer: String,
+    generated_at: String,
+    entries: &'a [LedgerEntry],
+}
+
+/// `GET /manifest`: the ledger as one document, signed by the payer so an
+/// auditor can check it came from this service.
+#[derive(Debug, Serialize)]
+pub struct SignedManifest {
+    /// Base58 public key of the payer.
+    pub signer: String,
+    /// Compact JSON of the manifest, exactly the bytes that were signed.
+    /// Kept as a string so re-serializing cannot change it.
+    pub manifest: String,
+    /// Base58 ed25519 signature by `signer` over the UTF-8 of `manifest`.
+    pub signature: String,
+}
+
+/// Serialize `entries` and sign the result with `payer`.
+pub fn sign_manifest(payer: &Keypair, entries: &[LedgerEntry]) -> Result<SignedManifest, String> {
+    let signer = payer.pubkey().to_string();
+    let manifest = serde_json::to_string(&ManifestBody {
+        signer: signer.clone(),
+        generated_at: Utc::now().to_rfc3339(),
+        entries,
+    })
+    .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    let signature = payer.sign_message(manifest.as_bytes()).to_string();
+    Ok(SignedManifest {
+        signer,
+        manifest,
+        signature,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mint::{build_memo, MintConfig, MintRequest};
+    use solana_pubkey::Pubkey;
+    use solana_signature::Signature;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_manifest_signature_verifies_against_payer() {
+        let payer = Keypair::new();
+        let entries = [LedgerEntry {
+            signature: "sig".into(),
+            signatures: vec!["sig".into()],
+            record: build_memo(
+                &MintRequest::builder("iceland-reykjavik").build(),
+                &MintConfig::default(),
+            ),
+            minted_at: Utc::now().to_rfc3339(),
+        }];
+
+        let signed = sign_manifest(&payer, &entries).unwrap();
+        let signer = Pubkey::from_str(&signed.signer).unwrap();
+        let signature = Signature::from_str(&signed.signature).unwrap();
+        assert_eq!(signer, payer.pubkey());
+        assert!(signature.verify(signer.as_ref(), signed.manifest.as_bytes()));
+        assert!(!signature.verify(signer.as_ref(), b"tampered"));
+
+        let body: serde_json::Value = serde_json::from_str(&signed.manifest).unwrap();
+        assert_eq!(
+            body["entries"][0]["record"]["location_id"],
+            "iceland-reykjavik"
+        );
+    }
+}
@@ -1,27 +1,522 @@
-use crate::rpc::RpcClient;
-use chrono::Utc;
+use crate::blockhash_cache::BlockhashCache;
+use crate::config::{env_or, env_parse};
+use crate::explorer::Cluster;
+use crate::rpc::{CommitmentLevel, LatestBlockhash, RpcClient, RpcError, SentTransaction};
+use crate::sequence::SequenceTracker;
+use crate::telemetry;
+use base64::Engine;
+use chrono::{DateTime, SecondsFormat, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_hash::Hash;
 use solana_instruction::{AccountMeta, Instruction};
 use solana_keypair::Keypair;
 use solana_pubkey::Pubkey;
 use solana_signer::Signer;
 use solana_transaction::Transaction;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{Read, Write};
 use std::str::FromStr;
 
-const MEMO_PROGRAM_ID: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
-const MEMO_MAX_BYTES: usize = 566;
+pub const MEMO_PROGRAM_ID: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+pub const MEMO_MAX_BYTES: usize = 566;
+/// Largest serialized transaction a validator accepts (one network packet).
+/// Signers and extra instructions count against it on top of the memos.
+pub const PACKET_DATA_SIZE: usize = 1232;
+const SOURCE_URL_MAX_BYTES: usize = 200;
+/// Room reserved for the `index/total/id:` header on split memos (up to 99
+/// chunks).
+const CHUNK_HEADER_MAX_BYTES: usize = 15;
+/// Bytes of the memo data's SHA-256 that identify a split record's chunks,
+/// so chunks of records minted concurrently are not mixed up.
+const CHUNK_ID_BYTES: usize = 4;
+/// Length of the `vXX:` version discriminator that optionally leads memo data.
+const VERSION_DISCRIMINATOR_LEN: usize = 4;
+/// Most compute units a single transaction may request.
+const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+pub const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
 
-#[derive(Debug, Deserialize, Serialize)]
+/// Digest used for server-computed `report_hash` values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgo {
+    #[default]
+    Sha256,
+    Blake3,
+    Keccak256,
+}
+
+impl FromStr for HashAlgo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sha256" => Ok(HashAlgo::Sha256),
+            "blake3" => Ok(HashAlgo::Blake3),
+            "keccak256" => Ok(HashAlgo::Keccak256),
+            other => Err(format!("Unknown hash algorithm: {}", other)),
+        }
+    }
+}
+
+impl HashAlgo {
+    /// Hex of the first 8 bytes of the digest of `data`.
+    fn digest_hex(self, data: &[u8]) -> String {
+        let digest: Vec<u8> = match self {
+            HashAlgo::Sha256 => Sha256::digest(data).to_vec(),
+            HashAlgo::Blake3 => blake3::hash(data).as_bytes().to_vec(),
+            HashAlgo::Keccak256 => Keccak256::digest(data).to_vec(),
+        };
+        hex::encode(&digest[..8])
+    }
+}
+
+/// What a mint returns when its transaction is submitted but not confirmed
+/// within the confirmation timeout.
+///
+/// - `error`: fail (504), so callers never mistake an unconfirmed record for
+///   a landed one. The transaction may still land, so a retry can duplicate
+///   the record.
+/// - `accept`: succeed with the signature and `confirmation_level`
+///   `"submitted"`, leaving the caller to check on it later. Never
+///   duplicates, but the record may never land.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConfirmTimeoutBehavior {
+    #[default]
+    Error,
+    Accept,
+}
+
+impl FromStr for ConfirmTimeoutBehavior {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(ConfirmTimeoutBehavior::Error),
+            "accept" => Ok(ConfirmTimeoutBehavior::Accept),
+            other => Err(format!("Unknown confirm timeout behavior: {}", other)),
+        }
+    }
+}
+
+/// Fractional seconds kept in server-generated record timestamps. `full`
+/// writes whatever the clock provides (up to nanoseconds); the shorter
+/// forms leave more of the memo for other fields.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampPrecision {
+    Seconds,
+    Millis,
+    #[default]
+    Full,
+}
+
+impl FromStr for TimestampPrecision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "seconds" => Ok(TimestampPrecision::Seconds),
+            "millis" => Ok(TimestampPrecision::Millis),
+            "full" => Ok(TimestampPrecision::Full),
+            other => Err(format!("Unknown timestamp precision: {}", other)),
+        }
+    }
+}
+
+impl TimestampPrecision {
+    fn format(self, time: DateTime<Utc>) -> String {
+        match self {
+            TimestampPrecision::Seconds => time.to_rfc3339_opts(SecondsFormat::Secs, false),
+            TimestampPrecision::Millis => time.to_rfc3339_opts(SecondsFormat::Millis, false),
+            TimestampPrecision::Full => time.to_rfc3339(),
+        }
+    }
+}
+
+/// Settings applied to every mint, loaded once at startup.
+#[derive(Debug, Clone)]
+pub struct MintConfig {
+    /// Oldest accepted `measured_at`, in seconds before now.
+    pub max_measured_age_secs: i64,
+    /// Furthest accepted `measured_at`, in seconds after now.
+    pub max_future_skew_secs: i64,
+    /// Fixed prefix written before the memo JSON so indexers can grep
+    /// program logs for records.
+    pub log_anchor: Option<String>,
+    /// Start memo data with a fixed-width `vXX:` discriminator (the record
+    /// version in hex), so indexers can route by version without parsing.
+    pub version_discriminator: bool,
+    /// JSON Schema URL written into each record as `schema`, when set.
+    pub schema_url: Option<String>,
+    /// Most transactions a single oversized record may be split across.
+    pub max_memo_chunks: usize,
+    /// Most items accepted in one `/mint/batch` request.
+    pub max_batch_size: usize,
+    /// Most `extra_memos` accepted on one request.
+    pub max_extra_memos: usize,
+    /// Algorithm for `report_hash` when the client does not supply one.
+    pub hash_algo: HashAlgo,
+    /// Simulate each transaction first and request only the compute units
+    /// it used, plus `compute_limit_margin_pct`.
+    pub auto_compute_limit: bool,
+    /// Headroom added to simulated compute units, in percent.
+    pub compute_limit_margin_pct: u64,
+    /// Compute unit limit set alongside a priority fee when it is not
+    /// simulated, and on the transactions `/instruction` returns.
+    pub compute_unit_limit: u32,
+    /// Cluster `explorer_url` links point at; set from the RPC URL.
+    pub cluster: Cluster,
+    /// Prefixes a `location_id` must start with; empty allows any.
+    pub allowed_location_prefixes: Vec<String>,
+    /// Whether a confirmation timeout fails the mint.
+    pub confirm_timeout_behavior: ConfirmTimeoutBehavior,
+    /// Fill in an omitted `name` from the `location_id`, e.g.
+    /// `iceland-reykjavik` becomes `Iceland Reykjavik`.
+    pub derive_name: bool,
+    /// Write each `/mint/batch` item's position into its record as `nonce`,
+    /// so identical items never produce identical transactions.
+    pub batch_nonce: bool,
+    /// Lamports the payer must keep after paying for a mint; a mint that
+    /// would dip below is rejected. Zero skips the balance check.
+    pub min_reserve_lamports: u64,
+    /// Precision of `timestamp` when the client omits `measured_at`.
+    pub timestamp_precision: TimestampPrecision,
+    /// Write compressed memo payloads in unpadded base64url rather than
+    /// standard base64. Both are read back either way.
+    pub memo_base64url: bool,
+}
+
+impl Default for MintConfig {
+    fn default() -> Self {
+        Self {
+            max_measured_age_secs: 30 * 24 * 3600,
+            max_future_skew_secs: 300,
+            log_anchor: None,
+            version_discriminator: false,
+            schema_url: None,
+            max_memo_chunks: 4,
+            max_batch_size: 50,
+            max_extra_memos: 4,
+            hash_algo: HashAlgo::Sha256,
+            auto_compute_limit: false,
+            compute_limit_margin_pct: 10,
+            compute_unit_limit: 200_000,
+            cluster: Cluster::Devnet,
+            allowed_location_prefixes: Vec::new(),
+            confirm_timeout_behavior: ConfirmTimeoutBehavior::Error,
+            derive_name: false,
+            batch_nonce: false,
+            min_reserve_lamports: 0,
+            timestamp_precision: TimestampPrecision::Full,
+            memo_base64url: false,
+        }
+    }
+}
+
+impl MintConfig {
+    /// Encodings of the memo body records may be written in: plain JSON,
+    /// or gzip in the configured base64 alphabet when a request sets
+    /// `compress`.
+    pub fn memo_encodings(&self) -> Vec<&'static str> {
+        let gzip = if self.memo_base64url {
+            "gzip+base64url"
+        } else {
+            "gzip+base64"
+        };
+        vec!["json", gzip]
+    }
+
+    /// Settings from the environment. Fails on an unparseable value for a
+    /// setting that picks between behaviours, such as `HASH_ALGO`.
+    pub fn from_env() -> Result<Self, String> {
+        let defaults = Self::default();
+        Ok(Self {
+            max_measured_age_secs: env_or("MAX_MEASURED_AGE_SECS", defaults.max_measured_age_secs),
+            max_future_skew_secs: env_or("MAX_FUTURE_SKEW_SECS", defaults.max_future_skew_secs),
+            log_anchor: std::env::var("LOG_ANCHOR").ok().filter(|s| !s.is_empty()),
+            version_discriminator: env_or(
+                "MEMO_VERSION_DISCRIMINATOR",
+                defaults.version_discriminator,
+            ),
+            schema_url: std::env::var("SCHEMA_URL").ok().filter(|s| !s.is_empty()),
+            max_memo_chunks: env_or("MAX_MEMO_CHUNKS", defaults.max_memo_chunks).min(99),
+            max_batch_size: env_or("MAX_BATCH_SIZE", defaults.max_batch_size),
+            max_extra_memos: env_or("MAX_EXTRA_MEMOS", defaults.max_extra_memos),
+            hash_algo: env_parse("HASH_ALGO", defaults.hash_algo)?,
+            auto_compute_limit: env_or("AUTO_COMPUTE_LIMIT", defaults.auto_compute_limit),
+            compute_limit_margin_pct: env_or(
+                "COMPUTE_LIMIT_MARGIN_PCT",
+                defaults.compute_limit_margin_pct,
+            ),
+            compute_unit_limit: env_or("COMPUTE_UNIT_LIMIT", defaults.compute_unit_limit)
+                .min(MAX_COMPUTE_UNIT_LIMIT),
+            cluster: defaults.cluster,
+            allowed_location_prefixes: std::env::var("ALLOWED_LOCATION_PREFIXES")
+                .map(|list| {
+                    list.split(',')
+                        .map(str::trim)
+                        .filter(|prefix| !prefix.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            confirm_timeout_behavior: env_parse(
+                "CONFIRM_TIMEOUT_BEHAVIOR",
+                defaults.confirm_timeout_behavior,
+            )?,
+            derive_name: env_or("DERIVE_NAME", defaults.derive_name),
+            batch_nonce: env_or("BATCH_NONCE", defaults.batch_nonce),
+            min_reserve_lamports: (env_or("MIN_RESERVE_SOL", 0.0) * LAMPORTS_PER_SOL) as u64,
+            timestamp_precision: env_parse("TIMESTAMP_PRECISION", defaults.timestamp_precision)?,
+            memo_base64url: env_or("MEMO_BASE64URL", defaults.memo_base64url),
+        })
+    }
+}
+
+/// Named delivery profiles bundling retry and confirmation behaviour.
+///
+/// - `reliable`: resubmit the signed transaction up to 3 times on failure and
+///   wait until it is finalized. Slowest, but the record is final on return.
+/// - `fast`: submit once and return as soon as the transaction is processed.
+///   Lowest latency; the record may still be rolled back.
+///
+/// Without a profile the transaction is submitted once and returned at the
+/// first commitment reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sla {
+    Reliable,
+    Fast,
+}
+
+/// How a signed transaction is submitted: attempts allowed and the
+/// commitment to wait for (`None` skips confirmation).
+struct SendPolicy {
+    max_attempts: u32,
+    target: Option<CommitmentLevel>,
+    /// `maxRetries` for the RPC node's own rebroadcasts.
+    max_retries: Option<usize>,
+    /// Return the signature unconfirmed instead of failing on a
+    /// confirmation timeout.
+    accept_timeout: bool,
+}
+
+impl SendPolicy {
+    fn for_request(req: &MintRequest, config: &MintConfig) -> Result<Self, String> {
+        let (max_attempts, mut target) = match req.sla {
+            Some(Sla::Reliable) => (3, CommitmentLevel::Finalized),
+            Some(Sla::Fast) | None => (1, CommitmentLevel::Processed),
+        };
+
+        let confirm = req.confirm.unwrap_or(true);
+        if req.require_finalized == Some(true) {
+            if !confirm {
+                return Err("require_finalized cannot be combined with confirm=false".into());
+            }
+            target = CommitmentLevel::Finalized;
+        }
+
+        Ok(Self {
+            max_attempts,
+            target: confirm.then_some(target),
+            max_retries: req.max_retries,
+            accept_timeout: config.confirm_timeout_behavior == ConfirmTimeoutBehavior::Accept,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct MintRequest {
     pub location_id: String,
+    /// Also accepted as `dc_name`.
+    #[serde(alias = "dc_name")]
     pub name: Option<String>,
     pub capacity_mw: Option<f64>,
+    /// Also accepted as `grade_letter`.
+    #[serde(alias = "grade_letter")]
     pub grade: Option<String>,
     pub report_hash: Option<String>,
+    /// RFC 3339 time the data was measured; defaults to the mint time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub measured_at: Option<String>,
+    /// http(s) URL of the dataset the record was derived from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    /// Position of this record in its location's series; must be the next
+    /// expected value when supplied and is assigned by the server otherwise.
+    /// Not part of the record's content, so left out of `report_hash`.
+    #[serde(default, skip_serializing)]
+    pub seq: Option<u64>,
+    /// Latitude in degrees, -90 to 90.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lat: Option<f64>,
+    /// Longitude in degrees, -180 to 180.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lon: Option<f64>,
+    /// Wait for the transaction to be confirmed (default). When false the
+    /// signature is returned as soon as the RPC node accepts the transaction.
+    #[serde(default, skip_serializing)]
+    pub confirm: Option<bool>,
+    /// Fail unless the transaction is finalized before the confirmation
+    /// timeout, instead of returning at a lower commitment.
+    #[serde(default, skip_serializing)]
+    pub require_finalized: Option<bool>,
+    /// Delivery profile; see `Sla`.
+    #[serde(default, skip_serializing)]
+    pub sla: Option<Sla>,
+    /// How many times the RPC node should rebroadcast the transaction
+    /// (`maxRetries`); defaults to `SEND_MAX_RETRIES`, or the node's choice.
+    #[serde(default, skip_serializing)]
+    pub max_retries: Option<usize>,
+    /// Priority fee per compute unit, in micro-lamports, added to every
+    /// transaction as a `SetComputeUnitPrice` instruction.
+    #[serde(default, skip_serializing)]
+    pub priority_fee_micro_lamports: Option<u64>,
+    /// Cluster for the response's `explorer_url` (`mainnet`, `testnet`,
+    /// `devnet` or an RPC URL), when it differs from the server's.
+    #[serde(default, skip_serializing)]
+    pub explorer_cluster: Option<String>,
+    /// Free-form memos written alongside the record, together in one
+    /// trailing transaction. Not part of the record or its hash.
+    #[serde(default, skip_serializing)]
+    pub extra_memos: Vec<String>,
+    /// Validate and build the record but submit nothing; see
+    /// `MintResponse::dry_run`.
+    #[serde(default, skip_serializing)]
+    pub dry_run: Option<bool>,
+    /// Set by `mint_batch` when `batch_nonce` is on; never read from clients.
+    #[serde(skip)]
+    pub nonce: Option<u64>,
+    /// Write the record gzipped and base64-encoded when that is shorter;
+    /// see `encode_memo_compressed`.
+    #[serde(default, skip_serializing)]
+    pub compress: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
+/// Fluent construction of a `MintRequest` for programmatic callers,
+/// leaving unset fields at their defaults.
+// The server only receives requests as JSON, and `check-wallet` builds
+// none, so outside tests nothing here is called yet.
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct MintRequestBuilder {
+    req: MintRequest,
+}
+
+#[allow(dead_code)]
+impl MintRequest {
+    pub fn builder(location_id: impl Into<String>) -> MintRequestBuilder {
+        MintRequestBuilder {
+            req: MintRequest {
+                location_id: location_id.into(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl MintRequestBuilder {
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.req.name = Some(name.into());
+        self
+    }
+
+    pub fn capacity_mw(mut self, capacity_mw: f64) -> Self {
+        self.req.capacity_mw = Some(capacity_mw);
+        self
+    }
+
+    pub fn grade(mut self, grade: impl Into<String>) -> Self {
+        self.req.grade = Some(grade.into());
+        self
+    }
+
+    pub fn report_hash(mut self, report_hash: impl Into<String>) -> Self {
+        self.req.report_hash = Some(report_hash.into());
+        self
+    }
+
+    pub fn measured_at(mut self, measured_at: impl Into<String>) -> Self {
+        self.req.measured_at = Some(measured_at.into());
+        self
+    }
+
+    pub fn source_url(mut self, source_url: impl Into<String>) -> Self {
+        self.req.source_url = Some(source_url.into());
+        self
+    }
+
+    pub fn seq(mut self, seq: u64) -> Self {
+        self.req.seq = Some(seq);
+        self
+    }
+
+    pub fn coordinates(mut self, lat: f64, lon: f64) -> Self {
+        self.req.lat = Some(lat);
+        self.req.lon = Some(lon);
+        self
+    }
+
+    pub fn confirm(mut self, confirm: bool) -> Self {
+        self.req.confirm = Some(confirm);
+        self
+    }
+
+    pub fn require_finalized(mut self) -> Self {
+        self.req.require_finalized = Some(true);
+        self
+    }
+
+    pub fn sla(mut self, sla: Sla) -> Self {
+        self.req.sla = Some(sla);
+        self
+    }
+
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.req.max_retries = Some(max_retries);
+        self
+    }
+
+    pub fn priority_fee_micro_lamports(mut self, fee: u64) -> Self {
+        self.req.priority_fee_micro_lamports = Some(fee);
+        self
+    }
+
+    pub fn explorer_cluster(mut self, cluster: impl Into<String>) -> Self {
+        self.req.explorer_cluster = Some(cluster.into());
+        self
+    }
+
+    pub fn extra_memo(mut self, memo: impl Into<String>) -> Self {
+        self.req.extra_memos.push(memo.into());
+        self
+    }
+
+    pub fn dry_run(mut self) -> Self {
+        self.req.dry_run = Some(true);
+        self
+    }
+
+    pub fn compress(mut self) -> Self {
+        self.req.compress = Some(true);
+        self
+    }
+
+    pub fn build(self) -> MintRequest {
+        self.req
+    }
+}
+
+/// The on-chain record. Fields serialize in declaration order, which is part
+/// of the format: append new fields at the end and keep them out of maps.
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MemoRecord {
     #[serde(rename = "type")]
     pub record_type: String,
@@ -32,130 +527,2735 @@ pub struct MemoRecord {
     pub feasibility_grade: Option<String>,
     pub timestamp: String,
     pub report_hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lat: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lon: Option<f64>,
+    /// Algorithm behind a server-computed `report_hash`. Omitted for sha256,
+    /// the original algorithm, and for client-supplied hashes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash_algo: Option<HashAlgo>,
+    /// JSON Schema the record conforms to, for self-describing records.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// Position within its `/mint/batch` request; see `batch_nonce`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
 }
 
-#[derive(Debug, Serialize)]
+/// Serializes in declaration order, like `MemoRecord`.
+#[derive(Debug, Clone, Serialize)]
 pub struct MintResponse {
     pub signature: String,
+    /// Every transaction the record was written in: the memo chunks in
+    /// order, then the one carrying `extra_memos`, if any. Holds just
+    /// `signature` unless the memo was split or had extra memos.
+    pub signatures: Vec<String>,
     pub memo_content: MemoRecord,
     pub explorer_url: String,
+    /// Commitment reached before returning, `"submitted"` when
+    /// confirmation was skipped, or `"dry_run"`.
+    pub confirmation_level: String,
+    /// Set when the request was a dry run: nothing was sent, so
+    /// `signature`, `signatures` and `explorer_url` are empty.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+    /// On dry runs, the memo exactly as it would have been written, before
+    /// any splitting into chunks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo_data: Option<String>,
+}
+
+/// How `/mint/batch` reacts to a failed item. Mints cannot be rolled back,
+/// so `fail_fast` only stops submitting the remaining items.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchMode {
+    /// Attempt every item and report each result.
+    #[default]
+    BestEffort,
+    /// Stop at the first failure; later items are not submitted.
+    FailFast,
+}
+
+/// What to do with a batch item repeating an earlier item's `location_id`
+/// and `measured_at`, which is almost always a client bug.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateMode {
+    /// Fail the repeat with code `duplicate` without submitting it.
+    #[default]
+    Reject,
+    /// Submit the repeat anyway and point `duplicate_of` at the original.
+    Mark,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchMintRequest {
+    pub items: Vec<MintRequest>,
+    #[serde(default)]
+    pub mode: BatchMode,
+    #[serde(default)]
+    pub duplicates: DuplicateMode,
 }
 
-fn hash_request(req: &MintRequest) -> String {
+/// Machine-readable category of a failed mint, so clients can retry only
+/// what is worth retrying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureCode {
+    /// The request itself is invalid; retrying will not help.
+    Validation,
+    /// The record does not fit in the allowed memo size, or its
+    /// transaction does not fit in a packet.
+    Size,
+    /// The RPC node rejected or failed the request.
+    Rpc,
+    /// The RPC node could not be reached or sent an unreadable response;
+    /// the request itself may be fine.
+    Network,
+    /// The transaction was not confirmed in time; it may still land.
+    Timeout,
+    /// The batch item repeats an earlier one.
+    Duplicate,
+    /// Paying for the mint would take the payer below `MIN_RESERVE_SOL`.
+    InsufficientFunds,
+}
+
+impl FailureCode {
+    /// The serialized name, e.g. for metric labels.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Validation => "validation",
+            Self::Size => "size",
+            Self::Rpc => "rpc",
+            Self::Network => "network",
+            Self::Timeout => "timeout",
+            Self::Duplicate => "duplicate",
+            Self::InsufficientFunds => "insufficient_funds",
+        }
+    }
+
+    /// Whether resubmitting the same request may succeed. A timed-out mint
+    /// may still land, so clients should check `/exists` before retrying.
+    pub fn is_retryable(self) -> bool {
+        matches!(self, Self::Network | Self::Timeout)
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{message}")]
+pub struct MintError {
+    pub code: FailureCode,
+    pub message: String,
+    /// Program logs from a failed simulation or preflight, which usually
+    /// explain the failure better than `message`.
+    pub logs: Vec<String>,
+    /// Signature of a transaction that was sent but not confirmed in time,
+    /// which may still land.
+    pub pending_signature: Option<String>,
+}
+
+impl MintError {
+    fn validation(message: impl Into<String>) -> Self {
+        Self {
+            code: FailureCode::Validation,
+            message: message.into(),
+            logs: Vec::new(),
+            pending_signature: None,
+        }
+    }
+
+    fn size(message: impl Into<String>) -> Self {
+        Self {
+            code: FailureCode::Size,
+            message: message.into(),
+            logs: Vec::new(),
+            pending_signature: None,
+        }
+    }
+
+    /// Classify an error from `RpcClient`, separating confirmation timeouts
+    /// and expiries, and unreachable nodes, from other RPC failures.
+    fn rpc(error: RpcError) -> Self {
+        Self {
+            code: match error {
+                RpcError::Timeout(_) | RpcError::Expired(_) => FailureCode::Timeout,
+                RpcError::Network(_) | RpcError::Parse(_) => FailureCode::Network,
+                RpcError::RpcReturned { .. } | RpcError::Failed { .. } => FailureCode::Rpc,
+            },
+            logs: error.logs(),
+            message: error.to_string(),
+            pending_signature: None,
+        }
+    }
+
+    /// `rpc`, remembering `signature` if `error` is a confirmation timeout,
+    /// after which the transaction may still land.
+    fn unconfirmed(error: RpcError, signature: &str) -> Self {
+        let pending = matches!(error, RpcError::Timeout(_)).then(|| signature.to_string());
+        Self {
+            pending_signature: pending,
+            ..Self::rpc(error)
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BatchOutcome {
+    Success(Box<MintResponse>),
+    Failed {
+        code: FailureCode,
+        message: String,
+        /// See `MintError::pending_signature`.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pending_signature: Option<String>,
+    },
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchItemResult {
+    pub index: usize,
+    #[serde(flatten)]
+    pub outcome: BatchOutcome,
+    /// Index of the earlier item with the same `location_id` and `measured_at`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchSummary {
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchMintResponse {
+    pub summary: BatchSummary,
+    /// One result per attempted item, in request order.
+    pub results: Vec<BatchItemResult>,
+    /// In `fail_fast` mode, the index of the failure that stopped the batch.
+    /// Items after it were not submitted.
+    pub stopped_at: Option<usize>,
+}
+
+fn hash_request(req: &MintRequest, algo: HashAlgo) -> String {
     let json = serde_json::to_string(req).unwrap_or_default();
-    let mut hasher = Sha256::new();
-    hasher.update(json.as_bytes());
-    let result = hasher.finalize();
-    hex::encode(&result[..8])
+    algo.digest_hex(json.as_bytes())
+}
+
+/// Reject a `capacity_mw` that would not serialize as a plain decimal:
+/// serde_json writes very large and very small floats in exponent form
+/// (e.g. `1e21`), which some memo readers cannot parse.
+fn validate_capacity(req: &MintRequest) -> Result<(), String> {
+    match req.capacity_mw {
+        Some(capacity) => validate_plain_number("capacity_mw", capacity),
+        None => Ok(()),
+    }
+}
+
+fn validate_plain_number(field: &str, value: f64) -> Result<(), String> {
+    if !value.is_finite() {
+        return Err(format!("{} must be finite, got {}", field, value));
+    }
+    let encoded = serde_json::to_string(&value).map_err(|e| e.to_string())?;
+    if encoded.contains(['e', 'E']) {
+        return Err(format!(
+            "{} {} would be written in scientific notation ({}); use a plain value",
+            field, value, encoded
+        ));
+    }
+    Ok(())
+}
+
+/// Reject coordinates outside the real latitude/longitude ranges.
+fn validate_coordinates(req: &MintRequest) -> Result<(), String> {
+    for (field, value, limit) in [("lat", req.lat, 90.0), ("lon", req.lon, 180.0)] {
+        let Some(value) = value else {
+            continue;
+        };
+        if !value.is_finite() || !(-limit..=limit).contains(&value) {
+            return Err(format!(
+                "{} must be between -{} and {}, got {}",
+                field, limit, limit, value
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Reject a `measured_at` that is unparseable or implausibly far from `now`.
+fn validate_measured_at(
+    req: &MintRequest,
+    config: &MintConfig,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let Some(measured_at) = &req.measured_at else {
+        return Ok(());
+    };
+
+    let measured = DateTime::parse_from_rfc3339(measured_at)
+        .map_err(|e| format!("Invalid measured_at {:?}: {}", measured_at, e))?;
+    let age_secs = (now - measured.with_timezone(&Utc)).num_seconds();
+
+    if age_secs > config.max_measured_age_secs {
+        return Err(format!(
+            "measured_at is {}s in the past (max {}s)",
+            age_secs, config.max_measured_age_secs
+        ));
+    }
+    if -age_secs > config.max_future_skew_secs {
+        return Err(format!(
+            "measured_at is {}s in the future (max {}s)",
+            -age_secs, config.max_future_skew_secs
+        ));
+    }
+    Ok(())
+}
+
+/// Extra memos share a single transaction, so besides the count cap their
+/// combined length must fit the memo limit.
+fn validate_extra_memos(req: &MintRequest, config: &MintConfig) -> Result<(), MintError> {
+    if req.extra_memos.len() > config.max_extra_memos {
+        return Err(MintError::validation(format!(
+            "Too many extra_memos ({}, max {})",
+            req.extra_memos.len(),
+            config.max_extra_memos
+        )));
+    }
+    let total: usize = req.extra_memos.iter().map(String::len).sum();
+    if total > MEMO_MAX_BYTES {
+        return Err(MintError::size(format!(
+            "extra_memos too large ({} bytes, max {})",
+            total, MEMO_MAX_BYTES
+        )));
+    }
+    Ok(())
+}
+
+fn validate_location_prefix(req: &MintRequest, config: &MintConfig) -> Result<(), String> {
+    let allowed = &config.allowed_location_prefixes;
+    if allowed.is_empty()
+        || allowed
+            .iter()
+            .any(|prefix| req.location_id.starts_with(prefix.as_str()))
+    {
+        return Ok(());
+    }
+    Err(format!(
+        "location_id {:?} does not start with an allowed prefix ({})",
+        req.location_id,
+        allowed.join(", ")
+    ))
+}
+
+/// Accept only reasonably short http(s) provenance URLs.
+fn validate_source_url(req: &MintRequest) -> Result<(), String> {
+    let Some(source_url) = &req.source_url else {
+        return Ok(());
+    };
+
+    if source_url.len() > SOURCE_URL_MAX_BYTES {
+        return Err(format!(
+            "source_url too long ({} bytes, max {})",
+            source_url.len(),
+            SOURCE_URL_MAX_BYTES
+        ));
+    }
+    let url = reqwest::Url::parse(source_url)
+        .map_err(|e| format!("Invalid source_url {:?}: {}", source_url, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "source_url must be http or https, got {:?}",
+            url.scheme()
+        ));
+    }
+    Ok(())
 }
 
-pub fn build_memo(req: &MintRequest) -> MemoRecord {
+pub fn build_memo(req: &MintRequest, config: &MintConfig) -> MemoRecord {
     MemoRecord {
         record_type: "orbital-atlas-dc-record".into(),
         version: 1,
         location_id: req.location_id.clone(),
-        name: req.name.clone(),
+        name: req.name.clone().or_else(|| {
+            config
+                .derive_name
+                .then(|| name_from_location_id(&req.location_id))
+        }),
         capacity_mw: req.capacity_mw,
         feasibility_grade: req.grade.clone(),
-        timestamp: Utc::now().to_rfc3339(),
-        report_hash: req.report_hash.clone().unwrap_or_else(|| hash_request(req)),
+        timestamp: req
+            .measured_at
+            .clone()
+            .unwrap_or_else(|| config.timestamp_precision.format(Utc::now())),
+        report_hash: req
+            .report_hash
+            .clone()
+            .unwrap_or_else(|| hash_request(req, config.hash_algo)),
+        seq: req.seq,
+        source_url: req.source_url.clone(),
+        lat: req.lat,
+        lon: req.lon,
+        hash_algo: (req.report_hash.is_none() && config.hash_algo != HashAlgo::Sha256)
+            .then_some(config.hash_algo),
+        schema: config.schema_url.clone(),
+        nonce: req.nonce,
     }
 }
 
-pub async fn mint(
-    rpc: &RpcClient,
-    payer: &Keypair,
-    req: &MintRequest,
-) -> Result<MintResponse, String> {
-    let memo_record = build_memo(req);
-    let memo_json = serde_json::to_string(&memo_record)
-        .map_err(|e| format!("Failed to serialize memo: {}", e))?;
+/// Title-case the words of a slug-style `location_id`.
+fn name_from_location_id(location_id: &str) -> String {
+    location_id
+        .split(['-', '_'])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-    if memo_json.len() > MEMO_MAX_BYTES {
-        return Err(format!(
-            "Memo too large ({} bytes, max {})",
-            memo_json.len(),
-            MEMO_MAX_BYTES
-        ));
+/// Marker `c` value of a compressed memo, `{"c":"gz","d":"<base64>"}`.
+const GZIP_MARKER: &str = "gz";
+
+/// Base64 of a binary memo payload, URL-safe and unpadded if configured.
+fn encode_payload(bytes: &[u8], config: &MintConfig) -> String {
+    if config.memo_base64url {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    } else {
+        base64::engine::general_purpose::STANDARD.encode(bytes)
     }
+}
 
-    let memo_program_id =
-        Pubkey::from_str(MEMO_PROGRAM_ID).map_err(|e| format!("Invalid memo program ID: {}", e))?;
+/// Decode a payload written by `encode_payload` under either setting. The
+/// two alphabets differ only in `+/` versus `-_`, so whichever pair
+/// appears picks the alphabet; padding is optional.
+fn decode_payload(payload: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    use base64::alphabet;
+    use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
 
-    let instruction = Instruction {
-        program_id: memo_program_id,
-        accounts: vec![AccountMeta::new_readonly(payer.pubkey(), true)],
-        data: memo_json.as_bytes().to_vec(),
+    let alphabet = if payload.contains(['-', '_']) {
+        &alphabet::URL_SAFE
+    } else {
+        &alphabet::STANDARD
     };
+    let config =
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+    GeneralPurpose::new(alphabet, config).decode(payload)
+}
 
-    let recent_blockhash = rpc.get_latest_blockhash().await?;
-
-    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
-    tx.sign(&[payer], recent_blockhash);
+/// Memo body holding a gzipped record JSON.
+#[derive(Debug, Serialize, Deserialize)]
+struct CompressedMemo {
+    c: String,
+    d: String,
+}
 
-    let signature = rpc.send_and_confirm_transaction(&tx).await?;
+/// Serialize a record into memo instruction data.
+pub fn encode_memo(record: &MemoRecord, config: &MintConfig) -> Result<String, String> {
+    let json =
+        serde_json::to_string(record).map_err(|e| format!("Failed to serialize memo: {}", e))?;
+    Ok(frame_memo(&json, record.version, config))
+}
 
-    Ok(MintResponse {
-        signature: signature.clone(),
-        memo_content: memo_record,
-        explorer_url: format!(
-            "https://explorer.solana.com/tx/{}?cluster=devnet",
-            signature
-        ),
+/// Like `encode_memo`, but with the record JSON replaced by a compressed
+/// memo body when that is shorter. The discriminator and log anchor stay
+/// in plain text so indexers can still find the record.
+fn encode_memo_compressed(record: &MemoRecord, config: &MintConfig) -> Result<String, String> {
+    let json =
+        serde_json::to_string(record).map_err(|e| format!("Failed to serialize memo: {}", e))?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder
+        .write_all(json.as_bytes())
+        .map_err(|e| format!("Failed to compress memo: {}", e))?;
+    let gzipped = encoder
+        .finish()
+        .map_err(|e| format!("Failed to compress memo: {}", e))?;
+    let compressed = serde_json::to_string(&CompressedMemo {
+        c: GZIP_MARKER.into(),
+        d: encode_payload(&gzipped, config),
     })
+    .map_err(|e| format!("Failed to serialize memo: {}", e))?;
+
+    let body = if compressed.len() < json.len() {
+        &compressed
+    } else {
+        &json
+    };
+    Ok(frame_memo(body, record.version, config))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Prefix a memo body with the version discriminator and log anchor, as
+/// configured.
+fn frame_memo(body: &str, version: u8, config: &MintConfig) -> String {
+    let discriminator = if config.version_discriminator {
+        version_discriminator(version)
+    } else {
+        String::new()
+    };
+    format!(
+        "{}{}{}",
+        discriminator,
+        config.log_anchor.as_deref().unwrap_or(""),
+        body
+    )
+}
 
-    #[test]
-    fn test_build_memo_structure() {
-        let req = MintRequest {
-            location_id: "iceland-reykjavik".into(),
-            name: Some("Test DC".into()),
-            capacity_mw: Some(50.0),
-            grade: Some("A".into()),
-            report_hash: None,
-        };
-        let memo = build_memo(&req);
-        assert_eq!(memo.record_type, "orbital-atlas-dc-record");
-        assert_eq!(memo.version, 1);
-        assert_eq!(memo.location_id, "iceland-reykjavik");
-        assert_eq!(memo.report_hash.len(), 16);
+/// The record JSON inside a memo body, decompressing it if it was written
+/// by `encode_memo_compressed`.
+fn decompress_memo(body: &str) -> Result<Cow<'_, str>, String> {
+    let compressed = match serde_json::from_str::<CompressedMemo>(body) {
+        Ok(compressed) if compressed.c == GZIP_MARKER => compressed,
+        _ => return Ok(Cow::Borrowed(body)),
+    };
+    let gzipped = decode_payload(&compressed.d)
+        .map_err(|e| format!("Compressed memo is not base64: {}", e))?;
+    let mut json = String::new();
+    GzDecoder::new(gzipped.as_slice())
+        .read_to_string(&mut json)
+        .map_err(|e| format!("Failed to decompress memo: {}", e))?;
+    Ok(Cow::Owned(json))
+}
+
+/// Memo data for `req`'s record, compressed if the request asks for it.
+fn encode_memo_for(
+    req: &MintRequest,
+    record: &MemoRecord,
+    config: &MintConfig,
+) -> Result<String, String> {
+    if req.compress.unwrap_or(false) {
+        encode_memo_compressed(record, config)
+    } else {
+        encode_memo(record, config)
     }
+}
 
-    #[test]
-    fn test_memo_size_within_limit() {
-        let req = MintRequest {
-            location_id: "iceland-reykjavik".into(),
-            name: Some("AcmeCorp Green-1".into()),
-            capacity_mw: Some(50.0),
-            grade: Some("A".into()),
-            report_hash: Some("abcdef1234567890".into()),
-        };
-        let memo = build_memo(&req);
-        let json = serde_json::to_string(&memo).unwrap();
-        assert!(
-            json.len() <= MEMO_MAX_BYTES,
-            "Memo {} bytes exceeds {} limit",
-            json.len(),
-            MEMO_MAX_BYTES
-        );
+fn version_discriminator(version: u8) -> String {
+    format!("v{:02x}:", version)
+}
+
+/// Parse memo instruction data back into a `MemoRecord`, stripping the
+/// version discriminator and log anchor if present and decompressing the
+/// record if needed. The discriminator is stripped whatever the current
+/// config, since records written under either setting may be on chain.
+pub fn decode_memo(data: &str, config: &MintConfig) -> Result<MemoRecord, String> {
+    let data = match data.get(..VERSION_DISCRIMINATOR_LEN) {
+        Some(prefix)
+            if prefix.starts_with('v')
+                && prefix.ends_with(':')
+                && prefix[1..3].bytes().all(|b| b.is_ascii_hexdigit()) =>
+        {
+            &data[VERSION_DISCRIMINATOR_LEN..]
+        }
+        _ => data,
+    };
+    let json = config
+        .log_anchor
+        .as_deref()
+        .and_then(|anchor| data.strip_prefix(anchor))
+        .unwrap_or(data);
+    let json = decompress_memo(json)?;
+    serde_json::from_str(&json).map_err(|e| format!("Memo is not a record: {}", e))
+}
+
+/// Split memo data that exceeds the memo limit into `index/total/id:`
+/// prefixed chunks, one per transaction, where `id` is the same for every
+/// chunk of `data`. Data that fits is returned unchanged.
+pub fn chunk_memo(data: &str) -> Vec<String> {
+    if data.len() <= MEMO_MAX_BYTES {
+        return vec![data.to_string()];
     }
 
-    #[test]
-    fn test_hash_deterministic() {
-        let req = MintRequest {
-            location_id: "test".into(),
-            name: None,
-            capacity_mw: None,
-            grade: None,
-            report_hash: None,
+    let capacity = MEMO_MAX_BYTES - CHUNK_HEADER_MAX_BYTES;
+    let mut parts = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let mut end = capacity.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (part, tail) = rest.split_at(end);
+        parts.push(part);
+        rest = tail;
+    }
+
+    let total = parts.len();
+    let id = hex::encode(&Sha256::digest(data)[..CHUNK_ID_BYTES]);
+    parts
+        .iter()
+        .enumerate()
+        .map(|(i, part)| format!("{}/{}/{}:{}", i + 1, total, id, part))
+        .collect()
+}
+
+/// Parse the `index/total/id:` header of a split memo, returning the index,
+/// total, record id and payload, or `None` if `data` is not a chunk. Chunks
+/// written before ids were added have an empty one.
+pub fn parse_chunk(data: &str) -> Option<(usize, usize, &str, &str)> {
+    let (header, payload) = data.split_once(':')?;
+    let (index, rest) = header.split_once('/')?;
+    let (total, id) = rest.split_once('/').unwrap_or((rest, ""));
+    Some((index.parse().ok()?, total.parse().ok()?, id, payload))
+}
+
+/// Rebuild memo data from chunks produced by `chunk_memo`, in any order.
+pub fn reassemble_chunks(chunks: &[String]) -> Result<String, String> {
+    let mut parts = Vec::with_capacity(chunks.len());
+    let mut record_id = None;
+    for chunk in chunks {
+        let (index, total, id, payload) =
+            parse_chunk(chunk).ok_or_else(|| format!("Invalid chunk header: {:?}", chunk))?;
+        if total != chunks.len() {
+            return Err(format!("Expected {} chunks, got {}", total, chunks.len()));
+        }
+        if *record_id.get_or_insert(id) != id {
+            return Err("Chunks belong to different records".into());
+        }
+        parts.push((index, payload));
+    }
+
+    parts.sort_by_key(|(index, _)| *index);
+    if parts
+        .iter()
+        .enumerate()
+        .any(|(i, (index, _))| *index != i + 1)
+    {
+        return Err("Missing or duplicate chunk index".into());
+    }
+    Ok(parts.into_iter().map(|(_, payload)| payload).collect())
+}
+
+/// Submit `tx` under `policy`, resubmitting the same signed transaction on
+/// failure. Resending is safe: a transaction can only land once, so a
+/// resubmission the node reports as already processed is the earlier
+/// attempt landing, not a new transaction.
+async fn send_with_policy(
+    rpc: &RpcClient,
+    tx: &Transaction,
+    policy: &SendPolicy,
+) -> Result<SentTransaction, RpcError> {
+    let signature = tx.signatures[0].to_string();
+    let mut attempt = 1;
+    loop {
+        let result = match rpc
+            .send_and_confirm_transaction(tx, policy.target, policy.max_retries)
+            .await
+        {
+            Err(e) if attempt > 1 && is_already_processed(&e) => {
+                tracing::info!("Resubmitted {} was already processed", signature);
+                let commitment = match policy.target {
+                    Some(target) => Some(
+                        rpc.confirm(&signature, target, Some(&tx.message.recent_blockhash))
+                            .await?,
+                    ),
+                    None => None,
+                };
+                Ok(SentTransaction {
+                    signature: signature.clone(),
+                    commitment,
+                })
+            }
+            Ok(sent) if sent.signature != signature => Err(RpcError::Parse(format!(
+                "RPC returned signature {} for transaction {}",
+                sent.signature, signature
+            ))),
+            result => result,
         };
-        let h1 = hash_request(&req);
-        let h2 = hash_request(&req);
-        assert_eq!(h1, h2);
-        assert_eq!(h1.len(), 16);
+        match result {
+            Ok(sent) => return Ok(sent),
+            Err(e @ RpcError::Timeout(_)) if policy.accept_timeout => {
+                tracing::warn!("Returning {} unconfirmed: {}", signature, e);
+                return Ok(SentTransaction {
+                    signature,
+                    commitment: None,
+                });
+            }
+            Err(e) if attempt < policy.max_attempts => {
+                tracing::warn!("Send attempt {} failed, retrying: {}", attempt, e);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Sign `instructions` into a transaction, rejecting one too large to send.
+/// Each memo fits the memo limit by construction, but extra signers and
+/// instructions can still push the whole transaction past a packet.
+fn sign_transaction(
+    payer: &Keypair,
+    author: &Keypair,
+    instructions: &[Instruction],
+    blockhash: LatestBlockhash,
+) -> Result<Transaction, MintError> {
+    tracing::debug!(
+        "Signing with blockhash {} (valid through block {})",
+        blockhash.hash,
+        blockhash.last_valid_block_height
+    );
+    let mut tx = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+    if author.pubkey() == payer.pubkey() {
+        tx.sign(&[payer], blockhash.hash);
+    } else {
+        tx.sign(&[payer, author], blockhash.hash);
+    }
+
+    let size = bincode::serialized_size(&tx)
+        .map_err(|e| MintError::validation(format!("Failed to serialize transaction: {}", e)))?
+        as usize;
+    if size > PACKET_DATA_SIZE {
+        let memo_bytes: usize = instructions
+            .iter()
+            .filter(|ix| ix.program_id.to_string() == MEMO_PROGRAM_ID)
+            .map(|ix| ix.data.len())
+            .sum();
+        return Err(MintError::size(format!(
+            "Transaction too large ({} bytes serialized, max {}); its memos are {} bytes \
+             and the rest is signatures, accounts and other instructions",
+            size, PACKET_DATA_SIZE, memo_bytes
+        )));
+    }
+    Ok(tx)
+}
+
+/// Compute unit limit sized from a simulation of `instructions` plus
+/// `margin_pct` percent, so the transaction reserves only what it uses.
+async fn simulated_compute_limit(
+    rpc: &RpcClient,
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    margin_pct: u64,
+) -> Result<u32, RpcError> {
+    let tx = Transaction::new_with_payer(instructions, Some(payer));
+    let units = rpc.simulate_transaction(&tx).await?;
+    let limit = units.saturating_mul(100 + margin_pct) / 100;
+    Ok(u32::try_from(limit)
+        .unwrap_or(u32::MAX)
+        .min(MAX_COMPUTE_UNIT_LIMIT))
+}
+
+/// `instructions` led by the compute budget instructions for `req`: a unit
+/// limit of `simulated_limit`, or `config.compute_unit_limit` without one,
+/// then a unit price when a priority fee is set. Unchanged when there is
+/// neither a priority fee nor a simulated limit to apply.
+fn with_compute_budget(
+    instructions: &[Instruction],
+    req: &MintRequest,
+    config: &MintConfig,
+    simulated_limit: Option<u32>,
+) -> Vec<Instruction> {
+    let mut budgeted = Vec::with_capacity(instructions.len() + 2);
+    if req.priority_fee_micro_lamports.is_some() || simulated_limit.is_some() {
+        let limit = simulated_limit.unwrap_or(config.compute_unit_limit);
+        budgeted.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+    }
+    if let Some(price) = req.priority_fee_micro_lamports {
+        budgeted.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+    }
+    budgeted.extend_from_slice(instructions);
+    budgeted
+}
+
+/// Whether a send error means the transaction's blockhash was too old for
+/// it to land: rejected in preflight, or expired while confirming.
+fn is_blockhash_expired(error: &RpcError) -> bool {
+    match error {
+        RpcError::Expired(_) => true,
+        RpcError::RpcReturned { message, .. } => message.contains("Blockhash not found"),
+        _ => false,
+    }
+}
+
+/// Whether a send error means the node has already seen this exact
+/// transaction.
+fn is_already_processed(error: &RpcError) -> bool {
+    match error {
+        RpcError::RpcReturned { message, data, .. } => {
+            message.contains("already been processed")
+                || data
+                    .as_ref()
+                    .is_some_and(|data| data.to_string().contains("AlreadyProcessed"))
+        }
+        _ => false,
+    }
+}
+
+/// Validate `req` and lay out its memo instructions, one `Vec` per
+/// transaction: the record's chunks in order, then any extra memos.
+fn build_instructions(
+    payer: &Pubkey,
+    req: &MintRequest,
+    config: &MintConfig,
+) -> Result<(MemoRecord, Vec<Vec<Instruction>>), MintError> {
+    validate_location_prefix(req, config).map_err(MintError::validation)?;
+    validate_measured_at(req, config, Utc::now()).map_err(MintError::validation)?;
+    validate_source_url(req).map_err(MintError::validation)?;
+    validate_capacity(req).map_err(MintError::validation)?;
+    validate_coordinates(req).map_err(MintError::validation)?;
+    validate_extra_memos(req, config)?;
+
+    let memo_record = build_memo(req, config);
+    let memo_json = encode_memo_for(req, &memo_record, config).map_err(MintError::validation)?;
+
+    let chunks = chunk_memo(&memo_json);
+    if chunks.len() > config.max_memo_chunks {
+        return Err(MintError::size(format!(
+            "Memo too large ({} bytes, max {} across {} transactions)",
+            memo_json.len(),
+            config.max_memo_chunks * (MEMO_MAX_BYTES - CHUNK_HEADER_MAX_BYTES),
+            config.max_memo_chunks
+        )));
+    }
+
+    let memo_program_id = Pubkey::from_str(MEMO_PROGRAM_ID)
+        .map_err(|e| MintError::validation(format!("Invalid memo program ID: {}", e)))?;
+
+    let memo_instruction = |data: &str| Instruction {
+        program_id: memo_program_id,
+        accounts: vec![AccountMeta::new_readonly(*payer, true)],
+        data: data.as_bytes().to_vec(),
+    };
+    let mut batches: Vec<Vec<Instruction>> = chunks
+        .iter()
+        .map(|chunk| vec![memo_instruction(chunk)])
+        .collect();
+    if !req.extra_memos.is_empty() {
+        batches.push(
+            req.extra_memos
+                .iter()
+                .map(|m| memo_instruction(m))
+                .collect(),
+        );
+    }
+    Ok((memo_record, batches))
+}
+
+/// `POST /instruction` body: a mint request plus the wallet that will sign.
+#[derive(Debug, Deserialize)]
+pub struct InstructionRequest {
+    pub signer: String,
+    #[serde(flatten)]
+    pub request: MintRequest,
+}
+
+/// How a transaction built from the returned instructions uses an account.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct AccountUsage {
+    pub pubkey: String,
+    pub signer: bool,
+    pub writable: bool,
+    pub fee_payer: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstructionResponse {
+    pub memo_content: MemoRecord,
+    /// Accounts of each transaction. The memo instruction lists the signer
+    /// as readonly, but as fee payer it is debited, so it is writable at the
+    /// transaction level.
+    pub accounts: Vec<AccountUsage>,
+    /// Base64 of each bincode-serialized `Instruction`, one list per
+    /// transaction in submission order. A record that fits one memo and has
+    /// no `extra_memos` is a single transaction with a single memo
+    /// instruction, led by the same compute budget instructions `/mint`
+    /// sends when a priority fee is set.
+    pub transactions: Vec<Vec<String>>,
+}
+
+/// The instructions `/mint` would submit for `req`, with `signer` as the
+/// signing account, for clients that sign and send themselves. There is no
+/// simulation here, so a compute unit limit is always the configured one.
+pub fn unsigned_instructions(
+    req: &InstructionRequest,
+    config: &MintConfig,
+) -> Result<InstructionResponse, MintError> {
+    let signer = Pubkey::from_str(&req.signer)
+        .map_err(|e| MintError::validation(format!("Invalid signer {:?}: {}", req.signer, e)))?;
+    let (memo_content, batches) = build_instructions(&signer, &req.request, config)?;
+    let transactions = batches
+        .iter()
+        .map(|instructions| {
+            with_compute_budget(instructions, &req.request, config, None)
+                .iter()
+                .map(|instruction| {
+                    bincode::serialize(instruction)
+                        .map(|bytes| base64::engine::general_purpose::STANDARD.encode(bytes))
+                        .map_err(|e| {
+                            MintError::validation(format!("Failed to serialize instruction: {}", e))
+                        })
+                })
+                .collect()
+        })
+        .collect::<Result<_, _>>()?;
+    let mut accounts = vec![
+        AccountUsage {
+            pubkey: signer.to_string(),
+            signer: true,
+            writable: true,
+            fee_payer: true,
+        },
+        AccountUsage {
+            pubkey: MEMO_PROGRAM_ID.to_string(),
+            signer: false,
+            writable: false,
+            fee_payer: false,
+        },
+    ];
+    if req.request.priority_fee_micro_lamports.is_some() {
+        accounts.push(AccountUsage {
+            pubkey: solana_compute_budget_interface::id().to_string(),
+            signer: false,
+            writable: false,
+            fee_payer: false,
+        });
+    }
+    Ok(InstructionResponse {
+        memo_content,
+        accounts,
+        transactions,
+    })
+}
+
+/// Fee in lamports for minting `req`, summed over every transaction it
+/// needs, priced against `blockhash`. `author` signs the memos when set;
+/// see `mint`. A priority fee is priced at `config.compute_unit_limit`.
+pub async fn estimate_fee(
+    rpc: &RpcClient,
+    payer: &Pubkey,
+    author: Option<&Pubkey>,
+    req: &MintRequest,
+    config: &MintConfig,
+    blockhash: Hash,
+) -> Result<u64, MintError> {
+    let (_, batches) = build_instructions(author.unwrap_or(payer), req, config)?;
+    let mut total = 0;
+    for instructions in &batches {
+        let instructions = with_compute_budget(instructions, req, config, None);
+        let mut tx = Transaction::new_with_payer(&instructions, Some(payer));
+        tx.message.recent_blockhash = blockhash;
+        total += rpc
+            .get_fee_for_message(&tx.message_data())
+            .await
+            .map_err(MintError::rpc)?;
+    }
+    Ok(total)
+}
+
+#[derive(Debug, Serialize)]
+pub struct ItemFee {
+    pub index: usize,
+    pub lamports: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchEstimateResponse {
+    pub total_lamports: u64,
+    pub items: Vec<ItemFee>,
+}
+
+/// Price every item of `batch` without submitting anything. Fails on the
+/// first item that could not be minted.
+pub async fn estimate_batch(
+    rpc: &RpcClient,
+    payer: &Pubkey,
+    author: Option<&Pubkey>,
+    batch: &BatchMintRequest,
+    config: &MintConfig,
+) -> Result<BatchEstimateResponse, MintError> {
+    if batch.items.len() > config.max_batch_size {
+        return Err(MintError::validation(format!(
+            "Batch too large ({} items, max {})",
+            batch.items.len(),
+            config.max_batch_size
+        )));
+    }
+
+    let blockhash = rpc.get_latest_blockhash().await.map_err(MintError::rpc)?;
+    let mut items = Vec::with_capacity(batch.items.len());
+    for (index, req) in batch.items.iter().enumerate() {
+        let lamports = estimate_fee(rpc, payer, author, req, config, blockhash)
+            .await
+            .map_err(|e| MintError {
+                code: e.code,
+                message: format!("Item {}: {}", index, e.message),
+                ..e
+            })?;
+        items.push(ItemFee { index, lamports });
+    }
+
+    Ok(BatchEstimateResponse {
+        total_lamports: items.iter().map(|item| item.lamports).sum(),
+        items,
+    })
+}
+
+/// Reject `req` if its estimated fee would leave the payer with less than
+/// `config.min_reserve_lamports`.
+async fn check_reserve(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    author: &Keypair,
+    req: &MintRequest,
+    config: &MintConfig,
+    blockhashes: &BlockhashCache,
+) -> Result<(), MintError> {
+    let balance = rpc
+        .get_balance(&payer.pubkey())
+        .await
+        .map_err(MintError::rpc)?;
+    let blockhash = blockhashes.get(rpc).await.map_err(MintError::rpc)?;
+    let fee = estimate_fee(
+        rpc,
+        &payer.pubkey(),
+        Some(&author.pubkey()),
+        req,
+        config,
+        blockhash.hash,
+    )
+    .await?;
+    if balance.saturating_sub(fee) < config.min_reserve_lamports {
+        return Err(MintError {
+            code: FailureCode::InsufficientFunds,
+            message: format!(
+                "Mint would leave {} lamports, below the {} lamport reserve",
+                balance.saturating_sub(fee),
+                config.min_reserve_lamports
+            ),
+            logs: Vec::new(),
+            pending_signature: None,
+        });
+    }
+    Ok(())
+}
+
+/// Mint `req`, signing with blockhashes from `blockhashes`. Nothing is
+/// fetched for a request that fails validation.
+///
+/// `payer` pays the fees. The memos are signed by `author` when given, so
+/// the record is attributed to it, and by `payer` otherwise.
+pub async fn mint(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    author: Option<&Keypair>,
+    req: &MintRequest,
+    config: &MintConfig,
+    blockhashes: &BlockhashCache,
+) -> Result<MintResponse, MintError> {
+    let author = author.unwrap_or(payer);
+    let (memo_record, batches) = build_instructions(&author.pubkey(), req, config)?;
+    let policy = SendPolicy::for_request(req, config).map_err(MintError::validation)?;
+    let cluster = match &req.explorer_cluster {
+        Some(cluster) => cluster.parse().map_err(MintError::validation)?,
+        None => config.cluster.clone(),
+    };
+
+    let memo_data = encode_memo_for(req, &memo_record, config).map_err(MintError::validation)?;
+    telemetry::record_memo_size(memo_data.len());
+
+    if req.dry_run.unwrap_or(false) {
+        return Ok(MintResponse {
+            signature: String::new(),
+            signatures: Vec::new(),
+            memo_content: memo_record,
+            explorer_url: String::new(),
+            confirmation_level: "dry_run".into(),
+            dry_run: true,
+            memo_data: Some(memo_data),
+        });
+    }
+
+    if config.min_reserve_lamports > 0 {
+        check_reserve(rpc, payer, author, req, config, blockhashes).await?;
+    }
+
+    let mut signatures = Vec::with_capacity(batches.len());
+    let mut commitment = None;
+    for (i, instructions) in batches.iter().enumerate() {
+        let simulated_limit = if config.auto_compute_limit {
+            let limit = simulated_compute_limit(
+                rpc,
+                &payer.pubkey(),
+                instructions,
+                config.compute_limit_margin_pct,
+            )
+            .await
+            .map_err(MintError::rpc)?;
+            Some(limit)
+        } else {
+            None
+        };
+        let instructions = with_compute_budget(instructions, req, config, simulated_limit);
+        // Fetched per chunk: confirming earlier chunks can take long enough
+        // for the cached hash to age out.
+        let blockhash = blockhashes.get(rpc).await.map_err(MintError::rpc)?;
+        let mut tx = sign_transaction(payer, author, &instructions, blockhash)?;
+        let sent = match send_with_policy(rpc, &tx, &policy).await {
+            // The transaction never landed, so it is safe to re-sign once
+            // with a fresh blockhash.
+            Err(e) if is_blockhash_expired(&e) => {
+                tracing::warn!("Blockhash {} expired; refreshing: {}", blockhash.hash, e);
+                let fresh = blockhashes.refresh(rpc).await.map_err(MintError::rpc)?;
+                tx = sign_transaction(payer, author, &instructions, fresh)?;
+                send_with_policy(rpc, &tx, &policy).await
+            }
+            result => result,
+        }
+        .map_err(|e| MintError::unconfirmed(e, &tx.signatures[0].to_string()))?;
+        signatures.push(sent.signature);
+        // Report the weakest commitment reached across all chunks.
+        commitment = if i == 0 {
+            sent.commitment
+        } else {
+            commitment.min(sent.commitment)
+        };
+    }
+    let signature = signatures[0].clone();
+
+    Ok(MintResponse {
+        signature: signature.clone(),
+        signatures,
+        memo_content: memo_record,
+        explorer_url: cluster.tx_url(&signature),
+        confirmation_level: commitment
+            .map_or("submitted", |level| level.as_str())
+            .to_string(),
+        dry_run: false,
+        memo_data: None,
+    })
+}
+
+/// Mint `req` as the next record in its location's sequence, rejecting a
+/// client-supplied `seq` that is out of order. Mints for the same location
+/// run one at a time so concurrent requests cannot claim the same `seq`.
+/// Every path that submits mints goes through here, so this is where they
+/// are counted in the `mints_*_total` metrics.
+pub async fn mint_sequenced(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    author: Option<&Keypair>,
+    mut req: MintRequest,
+    config: &MintConfig,
+    sequences: &SequenceTracker,
+    blockhashes: &BlockhashCache,
+) -> Result<MintResponse, MintError> {
+    let _guard = sequences.lock(&req.location_id).await;
+    let seq = sequences
+        .check(&req.location_id, req.seq)
+        .map_err(MintError::validation)?;
+    req.seq = Some(seq);
+
+    let dry_run = req.dry_run.unwrap_or(false);
+    if !dry_run {
+        telemetry::record_mint_attempt();
+    }
+    let response = match mint(rpc, payer, author, &req, config, blockhashes).await {
+        Ok(response) => response,
+        Err(e) => {
+            if !dry_run {
+                telemetry::record_mint_failure(e.code.as_str());
+            }
+            return Err(e);
+        }
+    };
+    if !dry_run {
+        telemetry::record_mint_success();
+        sequences.record(&req.location_id, seq);
+    }
+    Ok(response)
+}
+
+/// For each item, the index of the first earlier item with the same
+/// `location_id` and `measured_at`. Items without `measured_at` are stamped
+/// at mint time and never count as duplicates.
+fn find_duplicates(items: &[MintRequest]) -> Vec<Option<usize>> {
+    let mut first_seen: HashMap<(&str, &str), usize> = HashMap::new();
+    items
+        .iter()
+        .enumerate()
+        .map(|(index, req)| {
+            let measured_at = req.measured_at.as_deref()?;
+            let key = (req.location_id.as_str(), measured_at);
+            match first_seen.get(&key) {
+                Some(&original) => Some(original),
+                None => {
+                    first_seen.insert(key, index);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Mint each item in turn, collecting per-item results. Items share the
+/// blockhashes cached in `blockhashes`.
+pub async fn mint_batch(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    author: Option<&Keypair>,
+    batch: BatchMintRequest,
+    config: &MintConfig,
+    sequences: &SequenceTracker,
+    blockhashes: &BlockhashCache,
+) -> Result<BatchMintResponse, MintError> {
+    if batch.items.len() > config.max_batch_size {
+        return Err(MintError::validation(format!(
+            "Batch too large ({} items, max {})",
+            batch.items.len(),
+            config.max_batch_size
+        )));
+    }
+
+    let originals = find_duplicates(&batch.items);
+    let mut results = Vec::with_capacity(batch.items.len());
+    let mut stopped_at = None;
+    for (index, mut req) in batch.items.into_iter().enumerate() {
+        if config.batch_nonce {
+            req.nonce = Some(index as u64);
+        }
+        let duplicate_of = originals[index];
+        let outcome = match duplicate_of {
+            Some(original) if batch.duplicates == DuplicateMode::Reject => Err(MintError {
+                code: FailureCode::Duplicate,
+                message: format!(
+                    "Item {} repeats location_id and measured_at of item {}",
+                    index, original
+                ),
+                logs: Vec::new(),
+                pending_signature: None,
+            }),
+            _ => mint_sequenced(rpc, payer, author, req, config, sequences, blockhashes).await,
+        };
+        match outcome {
+            Ok(response) => results.push(BatchItemResult {
+                index,
+                outcome: BatchOutcome::Success(Box::new(response)),
+                duplicate_of,
+            }),
+            Err(e) => {
+                results.push(BatchItemResult {
+                    index,
+                    outcome: BatchOutcome::Failed {
+                        code: e.code,
+                        message: e.message,
+                        pending_signature: e.pending_signature,
+                    },
+                    duplicate_of,
+                });
+                if batch.mode == BatchMode::FailFast {
+                    stopped_at = Some(index);
+                    break;
+                }
+            }
+        }
+    }
+
+    let succeeded = results
+        .iter()
+        .filter(|result| matches!(result.outcome, BatchOutcome::Success(_)))
+        .count();
+    Ok(BatchMintResponse {
+        summary: BatchSummary {
+            succeeded,
+            failed: results.len() - succeeded,
+        },
+        results,
+        stopped_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_rpc::MockRpc;
+
+    #[test]
+    fn test_build_memo_structure() {
+        let req = MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            name: Some("Test DC".into()),
+            capacity_mw: Some(50.0),
+            grade: Some("A".into()),
+            report_hash: None,
+            ..Default::default()
+        };
+        let memo = build_memo(&req, &MintConfig::default());
+        assert_eq!(memo.record_type, "orbital-atlas-dc-record");
+        assert_eq!(memo.version, 1);
+        assert_eq!(memo.location_id, "iceland-reykjavik");
+        assert_eq!(memo.report_hash.len(), 16);
+    }
+
+    #[test]
+    fn test_derive_name_from_location_id() {
+        let req = MintRequest::builder("iceland-reykjavik").build();
+        assert_eq!(build_memo(&req, &MintConfig::default()).name, None);
+
+        let config = MintConfig {
+            derive_name: true,
+            ..Default::default()
+        };
+        let memo = build_memo(&req, &config);
+        assert_eq!(memo.name.as_deref(), Some("Iceland Reykjavik"));
+
+        let named = MintRequest::builder("iceland-reykjavik")
+            .name("Reykjavik DC")
+            .build();
+        assert_eq!(
+            build_memo(&named, &config).name.as_deref(),
+            Some("Reykjavik DC")
+        );
+    }
+
+    #[test]
+    fn test_memo_size_within_limit() {
+        let req = MintRequest::builder("iceland-reykjavik")
+            .name("AcmeCorp Green-1")
+            .capacity_mw(50.0)
+            .grade("A")
+            .report_hash("abcdef1234567890")
+            .build();
+        let memo = build_memo(&req, &MintConfig::default());
+        let json = serde_json::to_string(&memo).unwrap();
+        assert!(
+            json.len() <= MEMO_MAX_BYTES,
+            "Memo {} bytes exceeds {} limit",
+            json.len(),
+            MEMO_MAX_BYTES
+        );
+    }
+
+    #[test]
+    fn test_hash_deterministic() {
+        let req = MintRequest {
+            location_id: "test".into(),
+            name: None,
+            capacity_mw: None,
+            grade: None,
+            report_hash: None,
+            ..Default::default()
+        };
+        let h1 = hash_request(&req, HashAlgo::Sha256);
+        let h2 = hash_request(&req, HashAlgo::Sha256);
+        assert_eq!(h1, h2);
+        assert_eq!(h1.len(), 16);
+    }
+
+    #[test]
+    fn test_hash_unchanged_by_optional_and_assigned_fields() {
+        let mut req = MintRequest::builder("iceland-reykjavik")
+            .name("Reykjavik DC")
+            .capacity_mw(50.0)
+            .grade("A")
+            .build();
+        // The hash requests got before `measured_at`, `source_url` and `seq`
+        // existed.
+        assert_eq!(hash_request(&req, HashAlgo::Sha256), "6db6064b39424a9c");
+        req.seq = Some(7);
+        assert_eq!(hash_request(&req, HashAlgo::Sha256), "6db6064b39424a9c");
+    }
+
+    #[tokio::test]
+    async fn test_mint_without_confirmation_skips_polling() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        let payer = Keypair::new();
+        let req = MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            confirm: Some(false),
+            ..Default::default()
+        };
+
+        let resp = mint(
+            &rpc,
+            &payer,
+            None,
+            &req,
+            &MintConfig::default(),
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.confirmation_level, "submitted");
+        assert!(!resp.signature.is_empty());
+        assert_eq!(mock.calls("sendTransaction"), 1);
+        assert_eq!(mock.calls("getSignatureStatuses"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_builds_record_without_submitting() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        let config = MintConfig::default();
+        let sequences = SequenceTracker::default();
+        let req = MintRequest::builder("iceland-reykjavik").dry_run().build();
+
+        let resp = mint_sequenced(
+            &rpc,
+            &Keypair::new(),
+            None,
+            req,
+            &config,
+            &sequences,
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+        assert!(resp.dry_run);
+        assert_eq!(resp.confirmation_level, "dry_run");
+        assert!(resp.signature.is_empty() && resp.signatures.is_empty());
+        assert_eq!(
+            resp.memo_data.unwrap(),
+            encode_memo(&resp.memo_content, &config).unwrap()
+        );
+        assert_eq!(mock.calls("sendTransaction"), 0);
+        assert_eq!(mock.calls("getLatestBlockhash"), 0);
+        // The dry run's seq is still free for the real mint.
+        assert_eq!(sequences.next("iceland-reykjavik"), 1);
+    }
+
+    fn measured_at_request(offset_secs: i64, now: DateTime<Utc>) -> MintRequest {
+        MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            measured_at: Some((now + chrono::Duration::seconds(offset_secs)).to_rfc3339()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_measured_at_too_old_rejected() {
+        let now = Utc::now();
+        let config = MintConfig::default();
+        let req = measured_at_request(-config.max_measured_age_secs - 60, now);
+        let err = validate_measured_at(&req, &config, now).unwrap_err();
+        assert!(err.contains("in the past"), "{}", err);
+    }
+
+    #[test]
+    fn test_measured_at_too_far_in_future_rejected() {
+        let now = Utc::now();
+        let config = MintConfig::default();
+        let req = measured_at_request(config.max_future_skew_secs + 60, now);
+        let err = validate_measured_at(&req, &config, now).unwrap_err();
+        assert!(err.contains("in the future"), "{}", err);
+    }
+
+    #[test]
+    fn test_measured_at_in_range_accepted() {
+        let now = Utc::now();
+        let config = MintConfig::default();
+        assert!(validate_measured_at(&measured_at_request(-3600, now), &config, now).is_ok());
+        assert!(validate_measured_at(&measured_at_request(60, now), &config, now).is_ok());
+
+        let req = measured_at_request(-3600, now);
+        assert_eq!(
+            build_memo(&req, &MintConfig::default()).timestamp,
+            req.measured_at.unwrap()
+        );
+    }
+
+    #[test]
+    fn test_seconds_precision_timestamp_has_no_fraction() {
+        let config = MintConfig {
+            timestamp_precision: TimestampPrecision::Seconds,
+            ..Default::default()
+        };
+        let timestamp =
+            build_memo(&MintRequest::builder("iceland-reykjavik").build(), &config).timestamp;
+        assert!(!timestamp.contains('.'), "{}", timestamp);
+        assert!(
+            DateTime::parse_from_rfc3339(&timestamp).is_ok(),
+            "{}",
+            timestamp
+        );
+
+        let millis = TimestampPrecision::Millis.format(
+            DateTime::parse_from_rfc3339("2026-01-01T00:00:00.123456789Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        );
+        assert_eq!(millis, "2026-01-01T00:00:00.123+00:00");
+    }
+
+    #[test]
+    fn test_compressed_memo_round_trips_and_only_when_smaller() {
+        let config = MintConfig {
+            log_anchor: Some("OA1:".into()),
+            ..Default::default()
+        };
+        let req = MintRequest::builder("iceland-reykjavik")
+            .name("Reykjavik Geothermal Campus ".repeat(20))
+            .report_hash("ab".repeat(32))
+            .compress()
+            .build();
+        let record = build_memo(&req, &config);
+
+        let plain = encode_memo(&record, &config).unwrap();
+        let compressed = encode_memo_for(&req, &record, &config).unwrap();
+        assert!(plain.len() > MEMO_MAX_BYTES);
+        assert!(compressed.len() <= MEMO_MAX_BYTES, "{}", compressed.len());
+        assert!(
+            compressed.starts_with(r#"OA1:{"c":"gz","d":""#),
+            "{}",
+            compressed
+        );
+        let decoded = decode_memo(&compressed, &config).unwrap();
+        assert_eq!(
+            serde_json::to_string(&decoded).unwrap(),
+            serde_json::to_string(&record).unwrap()
+        );
+
+        let small = build_memo(&MintRequest::builder("iceland-reykjavik").build(), &config);
+        assert_eq!(
+            encode_memo_compressed(&small, &config).unwrap(),
+            encode_memo(&small, &config).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_base64url_payload_round_trips() {
+        // Encodes to `+/+/` in standard base64.
+        let bytes = [0xfb, 0xff, 0xbf];
+        let standard = encode_payload(&bytes, &MintConfig::default());
+        let url_config = MintConfig {
+            memo_base64url: true,
+            ..Default::default()
+        };
+        let url = encode_payload(&bytes, &url_config);
+        assert_eq!(standard, "+/+/");
+        assert_eq!(url, "-_-_");
+        assert_eq!(decode_payload(&standard).unwrap(), bytes);
+        assert_eq!(decode_payload(&url).unwrap(), bytes);
+        // Padding is optional under either alphabet.
+        assert_eq!(decode_payload("+/8=").unwrap(), [0xfb, 0xff]);
+        assert_eq!(decode_payload("-_8").unwrap(), [0xfb, 0xff]);
+
+        let req = MintRequest::builder("iceland-reykjavik")
+            .name("Reykjavik Geothermal Campus ".repeat(20))
+            .compress()
+            .build();
+        let record = build_memo(&req, &url_config);
+        let memo = encode_memo_for(&req, &record, &url_config).unwrap();
+        let payload: CompressedMemo = serde_json::from_str(&memo).unwrap();
+        assert!(!payload.d.contains(['+', '/', '=']), "{}", payload.d);
+        // Readers need not share the writer's setting.
+        let decoded = decode_memo(&memo, &MintConfig::default()).unwrap();
+        assert_eq!(
+            serde_json::to_string(&decoded).unwrap(),
+            serde_json::to_string(&record).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_log_anchor_prefixed_and_stripped() {
+        let config = MintConfig {
+            log_anchor: Some("orbital-atlas:".into()),
+            ..Default::default()
+        };
+        let req = MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            ..Default::default()
+        };
+        let data = encode_memo(&build_memo(&req, &config), &config).unwrap();
+        assert!(data.starts_with("orbital-atlas:{"));
+
+        let decoded = decode_memo(&data, &config).unwrap();
+        assert_eq!(decoded.location_id, "iceland-reykjavik");
+    }
+
+    #[test]
+    fn test_version_discriminator_leads_memo_and_is_skipped_on_decode() {
+        let config = MintConfig {
+            version_discriminator: true,
+            log_anchor: Some("orbital-atlas:".into()),
+            ..Default::default()
+        };
+        let req = MintRequest::builder("iceland-reykjavik").build();
+        let data = encode_memo(&build_memo(&req, &config), &config).unwrap();
+        assert!(data.starts_with("v01:orbital-atlas:{"), "{}", data);
+
+        let decoded = decode_memo(&data, &config).unwrap();
+        assert_eq!(decoded.location_id, "iceland-reykjavik");
+        // Readers without the setting still decode it.
+        let plain = MintConfig {
+            log_anchor: config.log_anchor.clone(),
+            ..Default::default()
+        };
+        assert!(decode_memo(&data, &plain).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_large_record_split_across_transactions() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        let payer = Keypair::new();
+        let req = MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            name: Some("Geothermal campus ".repeat(50)),
+            ..Default::default()
+        };
+
+        let resp = mint(
+            &rpc,
+            &payer,
+            None,
+            &req,
+            &MintConfig::default(),
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+        assert!(resp.signatures.len() > 1);
+        assert_eq!(resp.signature, resp.signatures[0]);
+        assert_eq!(mock.calls("sendTransaction"), resp.signatures.len());
+
+        let mut chunks: Vec<String> = mock
+            .sent_transactions()
+            .iter()
+            .map(|tx| String::from_utf8(tx.message.instructions[0].data.clone()).unwrap())
+            .collect();
+        assert!(chunks.iter().all(|c| c.len() <= MEMO_MAX_BYTES));
+        chunks.reverse();
+
+        let data = reassemble_chunks(&chunks).unwrap();
+        let record = decode_memo(&data, &MintConfig::default()).unwrap();
+        assert_eq!(record.name, req.name);
+    }
+
+    #[tokio::test]
+    async fn test_reliable_sla_retries_and_waits_for_finalized() {
+        let mock = MockRpc::start().await;
+        mock.respond_error("sendTransaction", "Node is behind");
+        mock.respond(
+            "getSignatureStatuses",
+            serde_json::json!({"value": [{"err": null, "confirmationStatus": "confirmed"}]}),
+        );
+        mock.respond(
+            "getSignatureStatuses",
+            serde_json::json!({"value": [{"err": null, "confirmationStatus": "finalized"}]}),
+        );
+        let req = MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            sla: Some(Sla::Reliable),
+            ..Default::default()
+        };
+
+        let rpc = RpcClient::new(&mock.url);
+        let resp = mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &req,
+            &MintConfig::default(),
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.confirmation_level, "finalized");
+        assert_eq!(mock.calls("sendTransaction"), 2);
+        assert_eq!(mock.calls("getSignatureStatuses"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_expired_blockhash_refreshed_and_retried_once() {
+        let mock = MockRpc::start().await;
+        mock.respond_error("sendTransaction", "Blockhash not found");
+        let rpc = RpcClient::new(&mock.url);
+        let blockhashes = BlockhashCache::default();
+        let req = MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            ..Default::default()
+        };
+
+        mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &req,
+            &MintConfig::default(),
+            &blockhashes,
+        )
+        .await
+        .unwrap();
+        assert_eq!(mock.calls("sendTransaction"), 2);
+        assert_eq!(mock.calls("getLatestBlockhash"), 2);
+
+        // The refreshed hash is cached for the next mint.
+        mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &req,
+            &MintConfig::default(),
+            &blockhashes,
+        )
+        .await
+        .unwrap();
+        assert_eq!(mock.calls("getLatestBlockhash"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_auto_compute_limit_uses_simulated_units_plus_margin() {
+        let mock = MockRpc::start().await;
+        mock.respond(
+            "simulateTransaction",
+            serde_json::json!({
+                "context": {"slot": 1},
+                "value": {"err": null, "logs": [], "unitsConsumed": 20_000}
+            }),
+        );
+        let rpc = RpcClient::new(&mock.url);
+        let config = MintConfig {
+            auto_compute_limit: true,
+            compute_limit_margin_pct: 10,
+            ..Default::default()
+        };
+        let req = MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            ..Default::default()
+        };
+
+        mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &req,
+            &config,
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+
+        let tx = &mock.sent_transactions()[0];
+        let expected = ComputeBudgetInstruction::set_compute_unit_limit(22_000);
+        let first = &tx.message.instructions[0];
+        assert_eq!(
+            tx.message.account_keys[first.program_id_index as usize],
+            expected.program_id
+        );
+        assert_eq!(first.data, expected.data);
+        assert_eq!(tx.message.instructions.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_simulation_logs_reported_in_error() {
+        let mock = MockRpc::start().await;
+        let logs = [
+            "Program MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr invoke [1]",
+            "Program log: Error: memo too long",
+        ];
+        mock.respond(
+            "simulateTransaction",
+            serde_json::json!({
+                "context": {"slot": 1},
+                "value": {"err": {"InstructionError": [0, "InvalidInstructionData"]}, "logs": logs}
+            }),
+        );
+        let rpc = RpcClient::new(&mock.url);
+        let config = MintConfig {
+            auto_compute_limit: true,
+            ..Default::default()
+        };
+
+        let err = mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &MintRequest::builder("iceland-reykjavik").build(),
+            &config,
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code, FailureCode::Rpc);
+        assert_eq!(err.logs, logs);
+    }
+
+    #[tokio::test]
+    async fn test_priority_fee_prepends_compute_unit_price() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        let req = MintRequest::builder("iceland-reykjavik");
+        let config = MintConfig::default();
+        let blockhashes = BlockhashCache::default();
+
+        let plain = req.clone().build();
+        mint(&rpc, &Keypair::new(), None, &plain, &config, &blockhashes)
+            .await
+            .unwrap();
+        let with_fee = req.priority_fee_micro_lamports(5_000).build();
+        mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &with_fee,
+            &config,
+            &blockhashes,
+        )
+        .await
+        .unwrap();
+
+        let sent = mock.sent_transactions();
+        assert_eq!(sent[0].message.instructions.len(), 1);
+        assert_eq!(sent[1].message.instructions.len(), 3);
+        let limit = ComputeBudgetInstruction::set_compute_unit_limit(config.compute_unit_limit);
+        assert_eq!(sent[1].message.instructions[0].data, limit.data);
+        let price = ComputeBudgetInstruction::set_compute_unit_price(5_000);
+        assert_eq!(sent[1].message.instructions[1].data, price.data);
+    }
+
+    #[tokio::test]
+    async fn test_explorer_cluster_override_applies_to_request() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        let config = MintConfig {
+            cluster: Cluster::Mainnet,
+            ..Default::default()
+        };
+        let req = MintRequest::builder("iceland-reykjavik")
+            .explorer_cluster("devnet")
+            .build();
+
+        let response = mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &req,
+            &config,
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+        assert!(
+            response.explorer_url.ends_with("?cluster=devnet"),
+            "{}",
+            response.explorer_url
+        );
+    }
+
+    #[test]
+    fn test_oversized_transaction_rejected_after_signing() {
+        let payer = Keypair::new();
+        let author = Keypair::new();
+        let blockhash = LatestBlockhash {
+            hash: Hash::new_unique(),
+            last_valid_block_height: 100,
+            fetched_at: tokio::time::Instant::now(),
+        };
+        let memo = |data: &str| Instruction {
+            program_id: Pubkey::from_str(MEMO_PROGRAM_ID).unwrap(),
+            accounts: vec![AccountMeta::new_readonly(author.pubkey(), true)],
+            data: data.as_bytes().to_vec(),
+        };
+
+        // Each memo is within the memo limit; together they overflow a packet.
+        let instructions = vec![
+            memo(&"x".repeat(MEMO_MAX_BYTES)),
+            memo(&"y".repeat(MEMO_MAX_BYTES)),
+        ];
+        let err = sign_transaction(&payer, &author, &instructions, blockhash).unwrap_err();
+        assert_eq!(err.code, FailureCode::Size);
+        assert!(
+            err.message.starts_with("Transaction too large")
+                && err
+                    .message
+                    .contains(&format!("memos are {} bytes", 2 * MEMO_MAX_BYTES)),
+            "{}",
+            err.message
+        );
+
+        let tx = sign_transaction(&payer, &author, &instructions[..1], blockhash).unwrap();
+        assert!(bincode::serialized_size(&tx).unwrap() as usize <= PACKET_DATA_SIZE);
+        assert!(tx.verify().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_author_signs_memo_while_payer_pays_fees() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        let payer = Keypair::new();
+        let author = Keypair::new();
+        let req = MintRequest::builder("iceland-reykjavik").build();
+
+        mint(
+            &rpc,
+            &payer,
+            Some(&author),
+            &req,
+            &MintConfig::default(),
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+
+        let tx = &mock.sent_transactions()[0];
+        assert_eq!(tx.signatures.len(), 2);
+        assert_eq!(tx.message.account_keys[0], payer.pubkey());
+        assert_eq!(tx.message.account_keys[1], author.pubkey());
+        assert!(tx.verify().is_ok());
+        let memo = &tx.message.instructions[0];
+        assert_eq!(memo.accounts, vec![1]);
+    }
+
+    #[tokio::test]
+    async fn test_mint_below_reserve_rejected() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        // The mock payer holds 1 SOL and each transaction costs 5000 lamports.
+        let config = MintConfig {
+            min_reserve_lamports: 999_996_000,
+            ..Default::default()
+        };
+        let req = MintRequest::builder("iceland-reykjavik").build();
+
+        let err = mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &req,
+            &config,
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code, FailureCode::InsufficientFunds);
+        assert!(err.message.contains("999995000"), "{}", err.message);
+        assert_eq!(mock.calls("sendTransaction"), 0);
+
+        let config = MintConfig {
+            min_reserve_lamports: 999_995_000,
+            ..Default::default()
+        };
+        mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &req,
+            &config,
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(mock.calls("sendTransaction"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_resubmitted_identical_transaction_counts_once() {
+        let mock = MockRpc::start().await;
+        // The first attempt lands but its confirmation fails; the retry is
+        // rejected as a duplicate of the landed transaction.
+        mock.respond_text("getSignatureStatuses", "not json");
+        mock.respond_default("sendTransaction");
+        mock.respond_error(
+            "sendTransaction",
+            "Transaction simulation failed: This transaction has already been processed",
+        );
+        let req = MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            sla: Some(Sla::Reliable),
+            ..Default::default()
+        };
+
+        let rpc = RpcClient::new(&mock.url).with_confirm_parse_retries(0);
+        mock.set_default(
+            "getSignatureStatuses",
+            serde_json::json!({"value": [{"err": null, "confirmationStatus": "finalized"}]}),
+        );
+        let resp = mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &req,
+            &MintConfig::default(),
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+
+        let sent = mock.sent_transactions();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0], sent[1]);
+        assert_eq!(resp.signatures, vec![sent[0].signatures[0].to_string()]);
+        assert_eq!(resp.confirmation_level, "finalized");
+    }
+
+    #[tokio::test]
+    async fn test_fast_sla_sends_once_and_accepts_processed() {
+        let mock = MockRpc::start().await;
+        mock.respond(
+            "getSignatureStatuses",
+            serde_json::json!({"value": [{"err": null, "confirmationStatus": "processed"}]}),
+        );
+        let req = MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            sla: Some(Sla::Fast),
+            ..Default::default()
+        };
+
+        let rpc = RpcClient::new(&mock.url);
+        let resp = mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &req,
+            &MintConfig::default(),
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.confirmation_level, "processed");
+        assert_eq!(mock.calls("sendTransaction"), 1);
+        assert_eq!(mock.calls("getSignatureStatuses"), 1);
+
+        mock.respond_error("sendTransaction", "Node is behind");
+        assert!(mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &req,
+            &MintConfig::default(),
+            &BlockhashCache::default()
+        )
+        .await
+        .is_err());
+        assert_eq!(mock.calls("sendTransaction"), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_require_finalized_times_out_with_signature() {
+        // The mock reports `confirmed` forever, so finalization never happens.
+        let mock = MockRpc::start().await;
+        let req = MintRequest::builder("iceland-reykjavik")
+            .require_finalized()
+            .build();
+
+        let rpc = RpcClient::new(&mock.url);
+        let err = mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &req,
+            &MintConfig::default(),
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap_err();
+        let signature = mock.sent_transactions()[0].signatures[0].to_string();
+        assert_eq!(err.code, FailureCode::Timeout);
+        assert!(err.message.contains(&signature), "{}", err);
+        assert!(err.message.contains("did not reach finalized"), "{}", err);
+        assert!(err.message.contains("last status: confirmed"), "{}", err);
+    }
+
+    /// Mint against a mock whose transactions are never seen.
+    async fn mint_never_confirming(
+        behavior: ConfirmTimeoutBehavior,
+    ) -> (MockRpc, Result<MintResponse, MintError>) {
+        let mock = MockRpc::start().await;
+        mock.set_default(
+            "getSignatureStatuses",
+            serde_json::json!({"context": {"slot": 1}, "value": [null]}),
+        );
+        let config = MintConfig {
+            confirm_timeout_behavior: behavior,
+            ..Default::default()
+        };
+        let req = MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            ..Default::default()
+        };
+        let rpc = RpcClient::new(&mock.url).with_confirm_timeout(std::time::Duration::from_secs(5));
+        let result = mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &req,
+            &config,
+            &BlockhashCache::default(),
+        )
+        .await;
+        (mock, result)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_confirm_timeout_error_fails_mint() {
+        let (mock, result) = mint_never_confirming(ConfirmTimeoutBehavior::Error).await;
+        let err = result.unwrap_err();
+        assert_eq!(err.code, FailureCode::Timeout);
+        assert_eq!(
+            err.pending_signature,
+            Some(mock.sent_transactions()[0].signatures[0].to_string())
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_confirm_timeout_accept_returns_signature() {
+        let (mock, result) = mint_never_confirming(ConfirmTimeoutBehavior::Accept).await;
+        let response = result.unwrap();
+        let signature = mock.sent_transactions()[0].signatures[0].to_string();
+        assert_eq!(response.signature, signature);
+        assert_eq!(response.confirmation_level, "submitted");
+    }
+
+    #[test]
+    fn test_require_finalized_conflicts_with_skipped_confirmation() {
+        let req = MintRequest::builder("iceland-reykjavik")
+            .confirm(false)
+            .require_finalized()
+            .build();
+        assert!(SendPolicy::for_request(&req, &MintConfig::default()).is_err());
+    }
+
+    fn batch_with_bad_middle_item(mode: BatchMode) -> BatchMintRequest {
+        let item = |location_id: &str, measured_at: Option<&str>| MintRequest {
+            location_id: location_id.into(),
+            measured_at: measured_at.map(Into::into),
+            ..Default::default()
+        };
+        BatchMintRequest {
+            items: vec![
+                item("iceland-reykjavik", None),
+                item("norway-oslo", Some("not a timestamp")),
+                item("sweden-lulea", None),
+            ],
+            mode,
+            duplicates: DuplicateMode::Reject,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_fail_fast_stops_after_first_failure() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        let batch = batch_with_bad_middle_item(BatchMode::FailFast);
+
+        let resp = mint_batch(
+            &rpc,
+            &Keypair::new(),
+            None,
+            batch,
+            &MintConfig::default(),
+            &SequenceTracker::default(),
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.results.len(), 2);
+        assert!(matches!(resp.results[0].outcome, BatchOutcome::Success(_)));
+        assert!(matches!(
+            resp.results[1].outcome,
+            BatchOutcome::Failed { .. }
+        ));
+        assert_eq!(resp.stopped_at, Some(1));
+        assert_eq!(mock.calls("sendTransaction"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_batch_best_effort_attempts_every_item() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        let batch = batch_with_bad_middle_item(BatchMode::BestEffort);
+
+        let resp = mint_batch(
+            &rpc,
+            &Keypair::new(),
+            None,
+            batch,
+            &MintConfig::default(),
+            &SequenceTracker::default(),
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.results.len(), 3);
+        assert!(matches!(resp.results[2].outcome, BatchOutcome::Success(_)));
+        assert_eq!(resp.stopped_at, None);
+        assert_eq!((resp.summary.succeeded, resp.summary.failed), (2, 1));
+        assert_eq!(mock.calls("sendTransaction"), 2);
+        // Both successful items were signed with a single fetched blockhash.
+        assert_eq!(mock.calls("getLatestBlockhash"), 1);
+    }
+
+    fn source_url_request(source_url: &str) -> MintRequest {
+        MintRequest::builder("iceland-reykjavik")
+            .source_url(source_url)
+            .build()
+    }
+
+    #[test]
+    fn test_source_url_valid() {
+        let req = source_url_request("https://data.example.org/datacenters.csv");
+        assert!(validate_source_url(&req).is_ok());
+        assert_eq!(
+            build_memo(&req, &MintConfig::default()).source_url,
+            req.source_url
+        );
+    }
+
+    #[test]
+    fn test_source_url_rejects_non_http_scheme() {
+        let err = validate_source_url(&source_url_request("ftp://data.example.org/x")).unwrap_err();
+        assert!(err.contains("http or https"), "{}", err);
+        assert!(validate_source_url(&source_url_request("javascript:alert(1)")).is_err());
+    }
+
+    #[test]
+    fn test_source_url_rejects_oversized() {
+        let url = format!("https://example.org/{}", "a".repeat(SOURCE_URL_MAX_BYTES));
+        let err = validate_source_url(&source_url_request(&url)).unwrap_err();
+        assert!(err.contains("too long"), "{}", err);
+    }
+
+    #[test]
+    fn test_builder_applies_defaults_to_unset_fields() {
+        let req = MintRequest::builder("iceland-reykjavik")
+            .capacity_mw(50.0)
+            .coordinates(64.1, -21.9)
+            .sla(Sla::Fast)
+            .build();
+
+        assert_eq!(req.location_id, "iceland-reykjavik");
+        assert_eq!(req.capacity_mw, Some(50.0));
+        assert_eq!((req.lat, req.lon), (Some(64.1), Some(-21.9)));
+        assert_eq!(req.sla, Some(Sla::Fast));
+        assert!(req.name.is_none() && req.report_hash.is_none() && req.confirm.is_none());
+        assert!(req.extra_memos.is_empty());
+    }
+
+    #[test]
+    fn test_location_prefix_allowlist() {
+        let config = MintConfig {
+            allowed_location_prefixes: vec!["iceland-".into(), "norway-".into()],
+            ..Default::default()
+        };
+        let req = |location_id: &str| MintRequest {
+            location_id: location_id.into(),
+            ..Default::default()
+        };
+        assert!(validate_location_prefix(&req("norway-oslo"), &config).is_ok());
+        let err = validate_location_prefix(&req("texas-austin"), &config).unwrap_err();
+        assert!(err.contains("allowed prefix"), "{}", err);
+        assert!(validate_location_prefix(&req("texas-austin"), &MintConfig::default()).is_ok());
+    }
+
+    #[test]
+    fn test_hash_algorithms_known_digests() {
+        assert_eq!(HashAlgo::Sha256.digest_hex(b"abc"), "ba7816bf8f01cfea");
+        assert_eq!(HashAlgo::Blake3.digest_hex(b"abc"), "6437b3ac38465133");
+        assert_eq!(HashAlgo::Keccak256.digest_hex(b"abc"), "4e03657aea45a94f");
+    }
+
+    #[test]
+    fn test_hash_algo_tagged_in_record() {
+        let req = MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            ..Default::default()
+        };
+        assert_eq!(build_memo(&req, &MintConfig::default()).hash_algo, None);
+
+        let config = MintConfig {
+            hash_algo: HashAlgo::Blake3,
+            ..Default::default()
+        };
+        let memo = build_memo(&req, &config);
+        assert_eq!(memo.hash_algo, Some(HashAlgo::Blake3));
+        assert_eq!(memo.report_hash, hash_request(&req, HashAlgo::Blake3));
+    }
+
+    #[tokio::test]
+    async fn test_batch_failure_codes_distinguish_validation_and_rpc() {
+        let mock = MockRpc::start().await;
+        // Twice, to outlast the retry with a refreshed blockhash.
+        mock.respond_error("sendTransaction", "Blockhash not found");
+        mock.respond_error("sendTransaction", "Blockhash not found");
+        let rpc = RpcClient::new(&mock.url);
+        let batch = BatchMintRequest {
+            items: vec![
+                MintRequest {
+                    location_id: "norway-oslo".into(),
+                    source_url: Some("ftp://example.org".into()),
+                    ..Default::default()
+                },
+                MintRequest {
+                    location_id: "iceland-reykjavik".into(),
+                    ..Default::default()
+                },
+            ],
+            mode: BatchMode::BestEffort,
+            duplicates: DuplicateMode::Reject,
+        };
+
+        let resp = mint_batch(
+            &rpc,
+            &Keypair::new(),
+            None,
+            batch,
+            &MintConfig::default(),
+            &SequenceTracker::default(),
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+        let codes: Vec<FailureCode> = resp
+            .results
+            .iter()
+            .map(|r| match &r.outcome {
+                BatchOutcome::Failed { code, .. } => *code,
+                BatchOutcome::Success(_) => panic!("item {} should fail", r.index),
+            })
+            .collect();
+        assert_eq!(codes, vec![FailureCode::Validation, FailureCode::Rpc]);
+
+        let json = serde_json::to_value(&resp.results[1]).unwrap();
+        assert_eq!(json["status"], "failed");
+        assert_eq!(json["code"], "rpc");
+    }
+
+    #[test]
+    fn test_mint_request_accepts_field_aliases() {
+        let req: MintRequest = serde_json::from_str(
+            r#"{"location_id": "ireland-dublin", "dc_name": "Dublin DC", "grade_letter": "A"}"#,
+        )
+        .unwrap();
+        assert_eq!(req.name.as_deref(), Some("Dublin DC"));
+        assert_eq!(req.grade.as_deref(), Some("A"));
+    }
+
+    #[tokio::test]
+    async fn test_extra_memos_at_cap_sent_in_trailing_transaction() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        let config = MintConfig {
+            max_extra_memos: 2,
+            ..MintConfig::default()
+        };
+        let req = MintRequest::builder("norway-oslo")
+            .extra_memo("audit:ok")
+            .extra_memo("ref:42")
+            .build();
+
+        let resp = mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &req,
+            &config,
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.signatures.len(), 2);
+        let sent = mock.sent_transactions();
+        assert_eq!(sent[1].message.instructions.len(), 2);
+        assert_eq!(sent[1].message.instructions[1].data, b"ref:42");
+    }
+
+    #[tokio::test]
+    async fn test_extra_memos_over_cap_rejected() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        let config = MintConfig {
+            max_extra_memos: 2,
+            ..MintConfig::default()
+        };
+        let req = MintRequest {
+            location_id: "norway-oslo".into(),
+            extra_memos: vec!["a".into(), "b".into(), "c".into()],
+            ..Default::default()
+        };
+
+        let err = mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &req,
+            &config,
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code, FailureCode::Validation);
+        assert!(err.message.contains("max 2"), "{}", err);
+        assert_eq!(mock.calls("sendTransaction"), 0);
+    }
+
+    fn batch_with_duplicate_pair(duplicates: DuplicateMode) -> BatchMintRequest {
+        let item = |location_id: &str| MintRequest {
+            location_id: location_id.into(),
+            measured_at: Some(Utc::now().to_rfc3339()),
+            ..Default::default()
+        };
+        let mut items = vec![item("norway-oslo"), item("sweden-lulea")];
+        items.push(MintRequest {
+            measured_at: items[0].measured_at.clone(),
+            ..item("norway-oslo")
+        });
+        BatchMintRequest {
+            items,
+            mode: BatchMode::BestEffort,
+            duplicates,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_rejects_duplicate_location_and_timestamp() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+
+        let resp = mint_batch(
+            &rpc,
+            &Keypair::new(),
+            None,
+            batch_with_duplicate_pair(DuplicateMode::Reject),
+            &MintConfig::default(),
+            &SequenceTracker::default(),
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            resp.results[2].outcome,
+            BatchOutcome::Failed {
+                code: FailureCode::Duplicate,
+                ..
+            }
+        ));
+        assert_eq!(resp.results[2].duplicate_of, Some(0));
+        assert_eq!(resp.results[1].duplicate_of, None);
+        assert_eq!(mock.calls("sendTransaction"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_batch_marks_duplicate_when_requested() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+
+        let resp = mint_batch(
+            &rpc,
+            &Keypair::new(),
+            None,
+            batch_with_duplicate_pair(DuplicateMode::Mark),
+            &MintConfig::default(),
+            &SequenceTracker::default(),
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(resp.results[2].outcome, BatchOutcome::Success(_)));
+        assert_eq!(resp.results[2].duplicate_of, Some(0));
+        assert_eq!(mock.calls("sendTransaction"), 3);
+    }
+
+    #[tokio::test]
+    async fn test_batch_nonce_makes_identical_items_distinct() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        let item = MintRequest::builder("norway-oslo")
+            .measured_at(Utc::now().to_rfc3339())
+            .report_hash("abcdef1234567890")
+            .build();
+        let config = MintConfig {
+            batch_nonce: true,
+            ..Default::default()
+        };
+
+        let resp = mint_batch(
+            &rpc,
+            &Keypair::new(),
+            None,
+            BatchMintRequest {
+                items: vec![item.clone(), item.clone(), item],
+                mode: BatchMode::BestEffort,
+                duplicates: DuplicateMode::Mark,
+            },
+            &config,
+            &SequenceTracker::default(),
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+        let minted: Vec<&MintResponse> = resp
+            .results
+            .iter()
+            .map(|result| match &result.outcome {
+                BatchOutcome::Success(minted) => minted.as_ref(),
+                BatchOutcome::Failed { message, .. } => panic!("{}", message),
+            })
+            .collect();
+        let nonces: Vec<_> = minted.iter().map(|m| m.memo_content.nonce).collect();
+        assert_eq!(nonces, [Some(0), Some(1), Some(2)]);
+        let signatures: std::collections::HashSet<_> =
+            minted.iter().map(|m| &m.signature).collect();
+        assert_eq!(signatures.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_estimate_batch_sums_fees() {
+        let mock = MockRpc::start().await;
+        mock.set_default("getFeeForMessage", serde_json::json!({"value": 5000}));
+        let rpc = RpcClient::new(&mock.url);
+        let item = |location_id: &str| MintRequest {
+            location_id: location_id.into(),
+            ..Default::default()
+        };
+        let batch = BatchMintRequest {
+            items: vec![
+                item("norway-oslo"),
+                MintRequest {
+                    extra_memos: vec!["audit:ok".into()],
+                    ..item("sweden-lulea")
+                },
+                item("iceland-reykjavik"),
+            ],
+            mode: BatchMode::BestEffort,
+            duplicates: DuplicateMode::Reject,
+        };
+
+        let estimate = estimate_batch(
+            &rpc,
+            &Keypair::new().pubkey(),
+            None,
+            &batch,
+            &MintConfig::default(),
+        )
+        .await
+        .unwrap();
+        let fees: Vec<u64> = estimate.items.iter().map(|item| item.lamports).collect();
+        assert_eq!(fees, vec![5000, 10000, 5000]);
+        assert_eq!(estimate.total_lamports, 20000);
+        assert_eq!(mock.calls("getLatestBlockhash"), 1);
+        assert_eq!(mock.calls("sendTransaction"), 0);
+    }
+
+    #[test]
+    fn test_schema_url_embedded_when_configured() {
+        let req = MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            ..Default::default()
+        };
+        let plain = encode_memo(
+            &build_memo(&req, &MintConfig::default()),
+            &MintConfig::default(),
+        )
+        .unwrap();
+        assert!(!plain.contains("schema"));
+
+        let config = MintConfig {
+            schema_url: Some("https://example.org/orbital-atlas/v1.json".into()),
+            ..MintConfig::default()
+        };
+        let memo = encode_memo(&build_memo(&req, &config), &config).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&memo).unwrap();
+        assert_eq!(json["schema"], "https://example.org/orbital-atlas/v1.json");
+    }
+
+    #[test]
+    fn test_capacity_in_scientific_notation_rejected() {
+        let req = |capacity_mw| MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            capacity_mw: Some(capacity_mw),
+            ..Default::default()
+        };
+
+        for capacity in [1e21, 1e-7] {
+            let err = validate_capacity(&req(capacity)).unwrap_err();
+            assert!(err.contains("scientific notation"), "{}", err);
+        }
+        for capacity in [0.0, 50.0, 1234567.25, 0.5] {
+            validate_capacity(&req(capacity)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_mint_response_field_order_is_stable() {
+        let req = MintRequest::builder("iceland-reykjavik")
+            .name("Reykjavik DC")
+            .capacity_mw(50.0)
+            .grade("A")
+            .report_hash("abcdef1234567890")
+            .measured_at("2026-01-01T00:00:00+00:00")
+            .seq(1)
+            .coordinates(64.1466, -21.9426)
+            .build();
+        let response = MintResponse {
+            signature: "sig".into(),
+            signatures: vec!["sig".into()],
+            memo_content: build_memo(&req, &MintConfig::default()),
+            explorer_url: "https://explorer.solana.com/tx/sig?cluster=devnet".into(),
+            confirmation_level: "confirmed".into(),
+            dry_run: false,
+            memo_data: None,
+        };
+
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            concat!(
+                r#"{"signature":"sig","signatures":["sig"],"memo_content":{"#,
+                r#""type":"orbital-atlas-dc-record","version":1,"location_id":"iceland-reykjavik","#,
+                r#""name":"Reykjavik DC","capacity_mw":50.0,"feasibility_grade":"A","#,
+                r#""timestamp":"2026-01-01T00:00:00+00:00","report_hash":"abcdef1234567890","#,
+                r#""seq":1,"lat":64.1466,"lon":-21.9426},"#,
+                r#""explorer_url":"https://explorer.solana.com/tx/sig?cluster=devnet","#,
+                r#""confirmation_level":"confirmed"}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_unsigned_instruction_matches_builder() {
+        let signer = Keypair::new().pubkey();
+        let req = InstructionRequest {
+            signer: signer.to_string(),
+            request: MintRequest {
+                location_id: "iceland-reykjavik".into(),
+                measured_at: Some(Utc::now().to_rfc3339()),
+                ..Default::default()
+            },
+        };
+        let config = MintConfig::default();
+
+        let response = unsigned_instructions(&req, &config).unwrap();
+        let (_, batches) = build_instructions(&signer, &req.request, &config).unwrap();
+        assert_eq!(response.transactions.len(), 1);
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&response.transactions[0][0])
+            .unwrap();
+        let instruction: Instruction = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(instruction, batches[0][0]);
+        assert_eq!(
+            instruction.accounts,
+            vec![AccountMeta::new_readonly(signer, true)]
+        );
+        assert_eq!(
+            response.accounts[0],
+            AccountUsage {
+                pubkey: signer.to_string(),
+                signer: true,
+                writable: true,
+                fee_payer: true,
+            }
+        );
+
+        let bad = InstructionRequest {
+            signer: "not-a-pubkey".into(),
+            request: MintRequest::default(),
+        };
+        assert!(unsigned_instructions(&bad, &config).is_err());
+    }
+
+    #[test]
+    fn test_unsigned_instructions_carry_compute_budget() {
+        let signer = Keypair::new().pubkey();
+        let req = InstructionRequest {
+            signer: signer.to_string(),
+            request: MintRequest::builder("iceland-reykjavik")
+                .priority_fee_micro_lamports(5_000)
+                .build(),
+        };
+        let config = MintConfig {
+            compute_unit_limit: 50_000,
+            ..Default::default()
+        };
+
+        let response = unsigned_instructions(&req, &config).unwrap();
+        let decoded: Vec<Instruction> = response.transactions[0]
+            .iter()
+            .map(|encoded| {
+                let bytes = base64::engine::general_purpose::STANDARD
+                    .decode(encoded)
+                    .unwrap();
+                bincode::deserialize(&bytes).unwrap()
+            })
+            .collect();
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(
+            decoded[0],
+            ComputeBudgetInstruction::set_compute_unit_limit(50_000)
+        );
+        assert_eq!(
+            decoded[1],
+            ComputeBudgetInstruction::set_compute_unit_price(5_000)
+        );
+        assert_eq!(decoded[2].program_id.to_string(), MEMO_PROGRAM_ID);
+        assert!(response
+            .accounts
+            .iter()
+            .any(|account| account.pubkey == solana_compute_budget_interface::id().to_string()));
+    }
+
+    #[test]
+    fn test_coordinates_in_record() {
+        let req = MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            lat: Some(64.1466),
+            lon: Some(-21.9426),
+            ..Default::default()
+        };
+        validate_coordinates(&req).unwrap();
+        let memo = build_memo(&req, &MintConfig::default());
+        assert_eq!((memo.lat, memo.lon), (Some(64.1466), Some(-21.9426)));
+
+        let plain = MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            ..Default::default()
+        };
+        let json = encode_memo(
+            &build_memo(&plain, &MintConfig::default()),
+            &MintConfig::default(),
+        )
+        .unwrap();
+        assert!(!json.contains("\"lat\""));
+    }
+
+    #[test]
+    fn test_out_of_range_coordinates_rejected() {
+        let req = |lat, lon| MintRequest {
+            location_id: "iceland-reykjavik".into(),
+            lat,
+            lon,
+            ..Default::default()
+        };
+
+        for (lat, lon) in [
+            (Some(90.5), None),
+            (None, Some(-180.1)),
+            (Some(f64::NAN), None),
+        ] {
+            assert!(validate_coordinates(&req(lat, lon)).is_err());
+        }
+        validate_coordinates(&req(Some(-90.0), Some(180.0))).unwrap();
+        // Serialized as `1e-5`, but still a valid coordinate.
+        validate_coordinates(&req(Some(0.00001), Some(-0.00001))).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mint_forwards_max_retries() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        let req = MintRequest::builder("iceland-reykjavik")
+            .max_retries(2)
+            .build();
+
+        mint(
+            &rpc,
+            &Keypair::new(),
+            None,
+            &req,
+            &MintConfig::default(),
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+        let params = mock.last_params("sendTransaction").unwrap();
+        assert_eq!(params[1]["maxRetries"], 2);
     }
 }
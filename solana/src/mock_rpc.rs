@@ -0,0 +1,256 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use base64::Engine;
+use serde_json::{json, Value};
+use solana_hash::Hash;
+use solana_transaction::Transaction;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// In-process JSON-RPC server for tests.
+///
+/// Answers the methods the service uses with canned defaults (a fixed
+/// blockhash, the submitted transaction's own signature, an immediately
+/// confirmed status). Tests can replace a default for the rest of the run,
+/// queue one-off replies per method, inject JSON-RPC errors or HTTP failures,
+/// and inspect what was called.
+pub struct MockRpc {
+    pub url: String,
+    state: Arc<Mutex<MockState>>,
+}
+
+enum Reply {
+    Default,
+    Body(Value),
+    Delayed(Duration, Value),
+    Text(String),
+    Status(StatusCode),
+    Disconnect,
+}
+
+#[derive(Default)]
+struct MockState {
+    calls: Vec<(String, Value)>,
+    queued: HashMap<String, VecDeque<Reply>>,
+    defaults: HashMap<String, Value>,
+    in_flight: usize,
+    peak_in_flight: usize,
+}
+
+impl MockRpc {
+    pub async fn start() -> Self {
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let app = Router::new()
+            .route("/", post(handle))
+            .with_state(state.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        Self { url, state }
+    }
+
+    fn queue(&self, method: &str, reply: Reply) {
+        self.state
+            .lock()
+            .unwrap()
+            .queued
+            .entry(method.to_string())
+            .or_default()
+            .push_back(reply);
+    }
+
+    /// Replace the canned `result` returned for `method` once its queue is empty.
+    pub fn set_default(&self, method: &str, result: Value) {
+        self.state
+            .lock()
+            .unwrap()
+            .defaults
+            .insert(method.to_string(), result);
+    }
+
+    /// Queue a `result` for the next call to `method`.
+    pub fn respond(&self, method: &str, result: Value) {
+        self.queue(
+            method,
+            Reply::Body(json!({"jsonrpc": "2.0", "id": 1, "result": result})),
+        );
+    }
+
+    /// Queue the usual default reply for the next call to `method`, so
+    /// replies queued after it apply to later calls.
+    pub fn respond_default(&self, method: &str) {
+        self.queue(method, Reply::Default);
+    }
+
+    /// Queue a JSON-RPC error for the next call to `method`.
+    pub fn respond_error(&self, method: &str, message: &str) {
+        self.queue(
+            method,
+            Reply::Body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": {"code": -32005, "message": message}
+            })),
+        );
+    }
+
+    /// Queue a `result` for the next call to `method`, sent after `delay`.
+    pub fn respond_slow(&self, method: &str, delay: Duration, result: Value) {
+        self.queue(
+            method,
+            Reply::Delayed(delay, json!({"jsonrpc": "2.0", "id": 1, "result": result})),
+        );
+    }
+
+    /// Queue a non-JSON body (with status 200) for the next call to `method`.
+    pub fn respond_text(&self, method: &str, text: &str) {
+        self.queue(method, Reply::Text(text.to_string()));
+    }
+
+    /// Queue an HTTP-level failure (e.g. 503) for the next call to `method`.
+    pub fn fail_http(&self, method: &str, status: u16) {
+        self.queue(method, Reply::Status(StatusCode::from_u16(status).unwrap()));
+    }
+
+    /// Drop the connection without replying on the next call to `method`.
+    pub fn disconnect(&self, method: &str) {
+        self.queue(method, Reply::Disconnect);
+    }
+
+    /// Transactions submitted via `sendTransaction`, in order.
+    pub fn sent_transactions(&self) -> Vec<Transaction> {
+        self.state
+            .lock()
+            .unwrap()
+            .calls
+            .iter()
+            .filter(|(m, _)| m == "sendTransaction")
+            .filter_map(|(_, params)| decode_transaction(&params[0]))
+            .collect()
+    }
+
+    /// Params of the most recent call to `method`.
+    pub fn last_params(&self, method: &str) -> Option<Value> {
+        self.state
+            .lock()
+            .unwrap()
+            .calls
+            .iter()
+            .rev()
+            .find(|(m, _)| m == method)
+            .map(|(_, params)| params.clone())
+    }
+
+    /// Most requests that were being handled at the same time.
+    pub fn peak_concurrency(&self) -> usize {
+        self.state.lock().unwrap().peak_in_flight
+    }
+
+    /// Number of times `method` has been called.
+    pub fn calls(&self, method: &str) -> usize {
+        self.state
+            .lock()
+            .unwrap()
+            .calls
+            .iter()
+            .filter(|(m, _)| m == method)
+            .count()
+    }
+}
+
+async fn handle(State(state): State<Arc<Mutex<MockState>>>, Json(req): Json<Value>) -> Response {
+    let method = req["method"].as_str().unwrap_or_default().to_string();
+    let params = req["params"].clone();
+
+    let reply = {
+        let mut state = state.lock().unwrap();
+        state.calls.push((method.clone(), params.clone()));
+        state.in_flight += 1;
+        state.peak_in_flight = state.peak_in_flight.max(state.in_flight);
+        match state.queued.get_mut(&method).and_then(|q| q.pop_front()) {
+            Some(Reply::Default) | None => {
+                let result = state
+                    .defaults
+                    .get(&method)
+                    .cloned()
+                    .unwrap_or_else(|| default_result(&method, &params));
+                Reply::Body(json!({"jsonrpc": "2.0", "id": 1, "result": result}))
+            }
+            Some(reply) => reply,
+        }
+    };
+
+    let response = match reply {
+        Reply::Default => unreachable!("default replies are resolved above"),
+        Reply::Body(body) => Json(body).into_response(),
+        Reply::Delayed(delay, body) => {
+            tokio::time::sleep(delay).await;
+            Json(body).into_response()
+        }
+        Reply::Text(text) => text.into_response(),
+        Reply::Status(status) => {
+            (status, status.canonical_reason().unwrap_or_default()).into_response()
+        }
+        Reply::Disconnect => {
+            state.lock().unwrap().in_flight -= 1;
+            // Unwinding ends the connection's task, closing the socket; unlike
+            // `panic!` it prints nothing.
+            std::panic::resume_unwind(Box::new(()));
+        }
+    };
+    state.lock().unwrap().in_flight -= 1;
+    response
+}
+
+fn default_result(method: &str, params: &Value) -> Value {
+    match method {
+        "getLatestBlockhash" => json!({
+            "context": {"slot": 1},
+            "value": {"blockhash": Hash::default().to_string(), "lastValidBlockHeight": 1000}
+        }),
+        "sendTransaction" => json!(signature_of(&params[0])),
+        "getSignatureStatuses" => json!({
+            "context": {"slot": 1},
+            "value": [{"slot": 1, "confirmations": 0, "err": null, "confirmationStatus": "confirmed"}]
+        }),
+        "getBalance" => json!({"context": {"slot": 1}, "value": 1_000_000_000u64}),
+        "getSignaturesForAddress" => json!([]),
+        "isBlockhashValid" => json!({"context": {"slot": 1}, "value": true}),
+        "requestAirdrop" => json!("airdrop-signature"),
+        "getHealth" => json!("ok"),
+        "getGenesisHash" => json!(Hash::default().to_string()),
+        "getTransactionCount" => json!(268_435_456),
+        "getFeeForMessage" => json!({"context": {"slot": 1}, "value": 5000}),
+        "getMinimumBalanceForRentExemption" => json!(1_461_600),
+        "simulateTransaction" => json!({
+            "context": {"slot": 1},
+            "value": {"err": null, "logs": [], "unitsConsumed": 10_000}
+        }),
+        _ => Value::Null,
+    }
+}
+
+fn decode_transaction(tx_base64: &Value) -> Option<Transaction> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(tx_base64.as_str()?)
+        .ok()?;
+    bincode::deserialize(&bytes).ok()
+}
+
+/// Echo back the first signature of the submitted transaction, as a real
+/// node would.
+fn signature_of(tx_base64: &Value) -> String {
+    decode_transaction(tx_base64)
+        .and_then(|tx| tx.signatures.first().map(|s| s.to_string()))
+        .unwrap_or_default()
+}
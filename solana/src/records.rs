@@ -0,0 +1,447 @@
+use crate::mint::{decode_memo, parse_chunk, reassemble_chunks, MemoRecord, MintConfig};
+use crate::rpc::{RpcClient, RpcError};
+use crate::verify::memos_in_transaction;
+use serde::{Deserialize, Serialize};
+use solana_pubkey::Pubkey;
+use std::collections::HashMap;
+
+/// A memo record found on chain, with where it landed.
+#[derive(Debug, Serialize)]
+pub struct LocationRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub record: MemoRecord,
+}
+
+/// Query parameters for `/records/:location_id`.
+#[derive(Debug, Default, Deserialize)]
+pub struct RecordsQuery {
+    /// Only return records from slots after this one.
+    pub since_slot: Option<u64>,
+    /// `next_cursor` from a previous page: the signature to continue below.
+    /// Also accepted as `before`, its name in `getSignaturesForAddress`.
+    #[serde(alias = "before")]
+    pub cursor: Option<String>,
+    /// Maximum number of records to return.
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecordsPage {
+    pub records: Vec<LocationRecord>,
+    /// Pass as `cursor` to fetch the next page; absent once exhausted.
+    pub next_cursor: Option<String>,
+}
+
+const DEFAULT_RECORDS_LIMIT: usize = 100;
+
+/// The `memo` field of `getSignaturesForAddress` is prefixed with the memo
+/// length, e.g. `[123] {...}`.
+fn strip_memo_length(memo: &str) -> &str {
+    match memo.split_once("] ") {
+        Some((prefix, rest)) if prefix.starts_with('[') => rest,
+        _ => memo,
+    }
+}
+
+/// Collect records written by `address` in slots after `since_slot`, newest
+/// first, for `location_id` or, when `None`, for every location. Paging
+/// stops as soon as an older slot is seen. Records split across several
+/// transactions are reassembled, even when chunks of concurrently minted
+/// records interleave, and reported under the signature of their first
+/// chunk.
+///
+/// At most `query.limit` records are returned; the cursor for the next page
+/// is the signature of the last one, which maps directly onto `before`.
+pub async fn records_since(
+    rpc: &RpcClient,
+    address: &Pubkey,
+    location_id: Option<&str>,
+    query: &RecordsQuery,
+    page_limit: usize,
+    config: &MintConfig,
+) -> Result<RecordsPage, RpcError> {
+    let since_slot = query.since_slot.unwrap_or(0);
+    let limit = query.limit.unwrap_or(DEFAULT_RECORDS_LIMIT).max(1);
+    let mut records = Vec::new();
+    let mut before = query.cursor.clone();
+    // Chunks seen so far by record id and chunk count.
+    let mut pending_chunks: HashMap<(String, usize), Vec<String>> = HashMap::new();
+    let done = |records| {
+        Ok(RecordsPage {
+            records,
+            next_cursor: None,
+        })
+    };
+
+    loop {
+        let page = rpc
+            .get_signatures_for_address(address, before.as_deref(), None, page_limit)
+            .await?;
+
+        for info in &page {
+            if info.slot <= since_slot {
+                return done(records);
+            }
+            if info.err.is_some() {
+                continue;
+            }
+            let Some(memo) = &info.memo else { continue };
+            let memo = strip_memo_length(memo);
+            let data = match parse_chunk(memo) {
+                Some((index, total, id, _)) => {
+                    let key = (id.to_string(), total);
+                    let chunks = pending_chunks.entry(key.clone()).or_default();
+                    // A repeated index means the chunks already buffered
+                    // belong to a record that never completed, e.g. one
+                    // whose other chunk failed, written without an id.
+                    if chunks
+                        .iter()
+                        .any(|chunk| parse_chunk(chunk).is_some_and(|(i, ..)| i == index))
+                    {
+                        chunks.clear();
+                    }
+                    chunks.push(memo.to_string());
+                    if chunks.len() < total {
+                        continue;
+                    }
+                    let chunks = pending_chunks.remove(&key).unwrap_or_default();
+                    match reassemble_chunks(&chunks) {
+                        Ok(data) => data,
+                        Err(_) => continue,
+                    }
+                }
+                None => memo.to_string(),
+            };
+            let Ok(record) = decode_memo(&data, config) else {
+                continue;
+            };
+            if location_id.is_none_or(|id| record.location_id == id) {
+                records.push(LocationRecord {
+                    signature: info.signature.clone(),
+                    slot: info.slot,
+                    block_time: info.block_time,
+                    record,
+                });
+                if records.len() >= limit {
+                    return Ok(RecordsPage {
+                        records,
+                        next_cursor: Some(info.signature.clone()),
+                    });
+                }
+            }
+        }
+
+        match page.last() {
+            Some(last) if page.len() >= page_limit => before = Some(last.signature.clone()),
+            _ => return done(records),
+        }
+    }
+}
+
+/// Why a record could not be read back by signature.
+#[derive(Debug)]
+pub enum RecordLookupError {
+    /// The node does not know the transaction.
+    NotFound(String),
+    /// The transaction exists but holds no complete record.
+    NoRecord(String),
+    Rpc(RpcError),
+}
+
+/// Read back the record written by the transaction `signature`.
+///
+/// Only whole records can be recovered this way: a chunk of a split record
+/// does not say where its other chunks are.
+pub async fn record_by_signature(
+    rpc: &RpcClient,
+    signature: &str,
+    config: &MintConfig,
+) -> Result<LocationRecord, RecordLookupError> {
+    let tx = rpc
+        .get_transaction(signature)
+        .await
+        .map_err(RecordLookupError::Rpc)?
+        .ok_or_else(|| {
+            RecordLookupError::NotFound(format!("Transaction {} not found", signature))
+        })?;
+    let memo = memos_in_transaction(&tx)
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            RecordLookupError::NoRecord(format!("Transaction {} has no memo", signature))
+        })?;
+    let data = match parse_chunk(&memo) {
+        Some((index, total, ..)) if total > 1 => {
+            return Err(RecordLookupError::NoRecord(format!(
+                "Transaction {} holds chunk {} of {} of a split record",
+                signature, index, total
+            )))
+        }
+        Some(_) => reassemble_chunks(&[memo]).map_err(RecordLookupError::NoRecord)?,
+        None => memo,
+    };
+    let record = decode_memo(&data, config).map_err(|e| {
+        RecordLookupError::NoRecord(format!("Memo in {} is not a record: {}", signature, e))
+    })?;
+
+    Ok(LocationRecord {
+        signature: signature.to_string(),
+        slot: tx["slot"].as_u64().unwrap_or_default(),
+        block_time: tx["blockTime"].as_i64(),
+        record,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_rpc::MockRpc;
+    use serde_json::json;
+
+    fn entry(slot: u64, location_id: &str) -> serde_json::Value {
+        let memo = format!(
+            r#"{{"type":"orbital-atlas-dc-record","version":1,"location_id":"{}","name":null,"capacity_mw":null,"feasibility_grade":null,"timestamp":"2026-01-01T00:00:00+00:00","report_hash":"abcdef1234567890"}}"#,
+            location_id
+        );
+        json!({
+            "signature": format!("sig-{}", slot),
+            "slot": slot,
+            "err": null,
+            "memo": format!("[{}] {}", memo.len(), memo),
+            "blockTime": 1_700_000_000 + slot as i64,
+        })
+    }
+
+    /// Entries for `location_id`'s record split into two chunks, in the
+    /// slots `first_slot` and `first_slot + 2`, newest first.
+    fn chunked_entries(first_slot: u64, location_id: &str) -> Vec<serde_json::Value> {
+        let memo = format!(
+            r#"{{"type":"orbital-atlas-dc-record","version":1,"location_id":"{}","name":"{}","capacity_mw":null,"feasibility_grade":null,"timestamp":"2026-01-01T00:00:00+00:00","report_hash":"abcdef1234567890"}}"#,
+            location_id,
+            "x".repeat(600)
+        );
+        let chunks = crate::mint::chunk_memo(&memo);
+        assert_eq!(chunks.len(), 2);
+        chunks
+            .iter()
+            .zip([first_slot, first_slot + 2])
+            .rev()
+            .map(|(chunk, slot)| {
+                json!({
+                    "signature": format!("sig-{}", slot),
+                    "slot": slot,
+                    "err": null,
+                    "memo": format!("[{}] {}", chunk.len(), chunk),
+                })
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_interleaved_chunked_records_both_reassembled() {
+        let mock = MockRpc::start().await;
+        let oslo = chunked_entries(102, "norway-oslo");
+        let reykjavik = chunked_entries(101, "iceland-reykjavik");
+        mock.respond(
+            "getSignaturesForAddress",
+            json!([&oslo[0], &reykjavik[0], &oslo[1], &reykjavik[1]]),
+        );
+        let rpc = RpcClient::new(&mock.url);
+
+        let page = records_since(
+            &rpc,
+            &Pubkey::new_unique(),
+            None,
+            &RecordsQuery::default(),
+            1000,
+            &MintConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        let found: Vec<(&str, &str)> = page
+            .records
+            .iter()
+            .map(|r| (r.signature.as_str(), r.record.location_id.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [("sig-102", "norway-oslo"), ("sig-101", "iceland-reykjavik")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_records_since_stops_at_boundary_slot() {
+        let mock = MockRpc::start().await;
+        mock.respond(
+            "getSignaturesForAddress",
+            json!([entry(105, "iceland-reykjavik"), entry(104, "norway-oslo")]),
+        );
+        mock.respond(
+            "getSignaturesForAddress",
+            json!([
+                entry(101, "iceland-reykjavik"),
+                entry(100, "iceland-reykjavik")
+            ]),
+        );
+        mock.respond(
+            "getSignaturesForAddress",
+            json!([
+                entry(99, "iceland-reykjavik"),
+                entry(98, "iceland-reykjavik")
+            ]),
+        );
+
+        let rpc = RpcClient::new(&mock.url);
+        let query = RecordsQuery {
+            since_slot: Some(100),
+            ..Default::default()
+        };
+        let page = records_since(
+            &rpc,
+            &Pubkey::new_unique(),
+            Some("iceland-reykjavik"),
+            &query,
+            2,
+            &MintConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        let slots: Vec<u64> = page.records.iter().map(|r| r.slot).collect();
+        assert_eq!(slots, vec![105, 101]);
+        assert_eq!(mock.calls("getSignaturesForAddress"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_records_without_location_lists_every_location() {
+        let mock = MockRpc::start().await;
+        mock.respond(
+            "getSignaturesForAddress",
+            json!([entry(105, "iceland-reykjavik"), entry(104, "norway-oslo")]),
+        );
+        let rpc = RpcClient::new(&mock.url);
+        let query: RecordsQuery =
+            serde_json::from_value(json!({"before": "sig-106", "limit": 5})).unwrap();
+
+        let page = records_since(
+            &rpc,
+            &Pubkey::new_unique(),
+            None,
+            &query,
+            1000,
+            &MintConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        let locations: Vec<&str> = page
+            .records
+            .iter()
+            .map(|r| r.record.location_id.as_str())
+            .collect();
+        assert_eq!(locations, ["iceland-reykjavik", "norway-oslo"]);
+        let params = mock.last_params("getSignaturesForAddress").unwrap();
+        assert_eq!(params[1]["before"], "sig-106");
+    }
+
+    #[tokio::test]
+    async fn test_records_cursor_pages_without_overlap() {
+        let mock = MockRpc::start().await;
+        let history: Vec<serde_json::Value> = (1..=5)
+            .rev()
+            .map(|slot| entry(slot, "iceland-reykjavik"))
+            .collect();
+        // Serve each `before` request the entries below that signature.
+        for skip in [0, 2, 4] {
+            mock.respond("getSignaturesForAddress", json!(history[skip..].to_vec()));
+        }
+
+        let rpc = RpcClient::new(&mock.url);
+        let address = Pubkey::new_unique();
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let query = RecordsQuery {
+                cursor: cursor.clone(),
+                limit: Some(2),
+                ..Default::default()
+            };
+            let page = records_since(
+                &rpc,
+                &address,
+                Some("iceland-reykjavik"),
+                &query,
+                1000,
+                &MintConfig::default(),
+            )
+            .await
+            .unwrap();
+
+            if let Some(cursor) = &cursor {
+                let params = mock.last_params("getSignaturesForAddress").unwrap();
+                assert_eq!(&params[1]["before"], cursor);
+            }
+            seen.extend(page.records.into_iter().map(|r| r.slot));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        assert_eq!(seen, vec![5, 4, 3, 2, 1]);
+    }
+
+    fn parsed_tx(instructions: serde_json::Value) -> serde_json::Value {
+        json!({
+            "slot": 42,
+            "blockTime": 1_700_000_042,
+            "transaction": {"message": {"instructions": instructions}}
+        })
+    }
+
+    #[tokio::test]
+    async fn test_record_by_signature_returns_record_and_block_time() {
+        let mock = MockRpc::start().await;
+        let memo = entry(1, "iceland-reykjavik")["memo"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        mock.respond(
+            "getTransaction",
+            parsed_tx(json!([{"program": "spl-memo", "parsed": strip_memo_length(&memo)}])),
+        );
+        let rpc = RpcClient::new(&mock.url);
+
+        let found = record_by_signature(&rpc, "sig", &MintConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(found.record.location_id, "iceland-reykjavik");
+        assert_eq!(found.slot, 42);
+        assert_eq!(found.block_time, Some(1_700_000_042));
+    }
+
+    #[tokio::test]
+    async fn test_record_by_signature_not_found_and_no_memo() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        let config = MintConfig::default();
+
+        // The mock answers `null` for unknown transactions.
+        assert!(matches!(
+            record_by_signature(&rpc, "missing", &config).await,
+            Err(RecordLookupError::NotFound(_))
+        ));
+
+        mock.respond(
+            "getTransaction",
+            parsed_tx(json!([{"program": "system", "parsed": {"type": "transfer"}}])),
+        );
+        assert!(matches!(
+            record_by_signature(&rpc, "transfer", &config).await,
+            Err(RecordLookupError::NoRecord(_))
+        ));
+    }
+}
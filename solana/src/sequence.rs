@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OwnedMutexGuard;
+
+type LocationLock = Arc<tokio::sync::Mutex<()>>;
+
+/// Per-location sequence numbers, so each location's records form a strictly
+/// ordered series. A gap or repeat in `seq` on chain means a record is
+/// missing or duplicated. Tracked in memory, so numbering restarts at 1 when
+/// the service restarts.
+#[derive(Default)]
+pub struct SequenceTracker {
+    last: Mutex<HashMap<String, u64>>,
+    /// Only locations with a mint in progress or waiting, so arbitrary
+    /// location ids cannot grow it without bound.
+    locks: Mutex<HashMap<String, LocationLock>>,
+}
+
+/// Exclusive use of one location's sequence, from `SequenceTracker::lock`.
+/// Dropping it forgets the location's lock once no one else needs it.
+pub struct SequenceGuard<'a> {
+    tracker: &'a SequenceTracker,
+    location_id: String,
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl Drop for SequenceGuard<'_> {
+    fn drop(&mut self) {
+        self.guard.take();
+        let mut locks = self.tracker.locks.lock().unwrap();
+        // Anyone else holding or waiting for the lock has a clone of it.
+        if locks
+            .get(&self.location_id)
+            .is_some_and(|lock| Arc::strong_count(lock) == 1)
+        {
+            locks.remove(&self.location_id);
+        }
+    }
+}
+
+impl SequenceTracker {
+    /// Wait for exclusive use of `location_id`'s sequence. Mints holding
+    /// the guard from `check` through `record` get consecutive numbers;
+    /// other locations are not blocked.
+    pub async fn lock(&self, location_id: &str) -> SequenceGuard<'_> {
+        let lock = self
+            .locks
+            .lock()
+            .unwrap()
+            .entry(location_id.to_string())
+            .or_default()
+            .clone();
+        SequenceGuard {
+            tracker: self,
+            location_id: location_id.to_string(),
+            guard: Some(lock.lock_owned().await),
+        }
+    }
+
+    /// The sequence number the next record for `location_id` must carry.
+    pub fn next(&self, location_id: &str) -> u64 {
+        self.last
+            .lock()
+            .unwrap()
+            .get(location_id)
+            .map_or(1, |last| last + 1)
+    }
+
+    /// Resolve the sequence number for a new record, rejecting a
+    /// client-supplied value that is not the expected next one.
+    pub fn check(&self, location_id: &str, supplied: Option<u64>) -> Result<u64, String> {
+        let expected = self.next(location_id);
+        match supplied {
+            Some(seq) if seq != expected => Err(format!(
+                "Out-of-order seq {} for {} (expected {})",
+                seq, location_id, expected
+            )),
+            _ => Ok(expected),
+        }
+    }
+
+    /// Record that `seq` was minted for `location_id`.
+    pub fn record(&self, location_id: &str, seq: u64) {
+        let mut last = self.last.lock().unwrap();
+        let entry = last.entry(location_id.to_string()).or_default();
+        *entry = (*entry).max(seq);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_location_lock_forgotten_once_released() {
+        let tracker = SequenceTracker::default();
+        let first = tracker.lock("iceland-reykjavik").await;
+        let waiting = tracker.lock("iceland-reykjavik");
+        tokio::pin!(waiting);
+        let timeout = std::time::Duration::from_millis(10);
+        assert!(tokio::time::timeout(timeout, &mut waiting).await.is_err());
+
+        drop(first);
+        let second = waiting.await;
+        assert_eq!(tracker.locks.lock().unwrap().len(), 1);
+        drop(second);
+        assert!(tracker.locks.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+use crate::blockhash_cache::BlockhashCache;
+use crate::explorer::Cluster;
+use crate::rpc::{CommitmentLevel, RpcClient, RpcError};
+use serde::{Deserialize, Serialize};
+use solana_instruction::{AccountMeta, Instruction};
+use solana_keypair::Keypair;
+use solana_pubkey::Pubkey;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use std::str::FromStr;
+
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+/// Size of an SPL Token mint account.
+const MINT_ACCOUNT_LEN: u64 = 82;
+
+/// Body of `POST /mint/token`.
+#[derive(Debug, Deserialize)]
+pub struct TokenMintRequest {
+    /// Wallet that receives the tokens, in an associated token account.
+    pub recipient: String,
+    pub decimals: u8,
+    /// Base units to mint, i.e. `amount / 10^decimals` whole tokens.
+    pub amount: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenMintResponse {
+    pub signature: String,
+    /// Address of the newly created mint.
+    pub mint: String,
+    /// The recipient's associated token account for `mint`.
+    pub token_account: String,
+    pub recipient: String,
+    pub decimals: u8,
+    pub amount: u64,
+    pub explorer_url: String,
+}
+
+fn program_id(id: &str) -> Pubkey {
+    Pubkey::from_str(id).expect("Program IDs are valid")
+}
+
+/// Associated token account of `wallet` for `mint`.
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            wallet.as_ref(),
+            program_id(TOKEN_PROGRAM_ID).as_ref(),
+            mint.as_ref(),
+        ],
+        &program_id(ASSOCIATED_TOKEN_PROGRAM_ID),
+    )
+    .0
+}
+
+/// System program `CreateAccount`: fund `new_account` with `lamports` and
+/// hand `space` bytes of it to `owner`.
+fn create_account(
+    payer: &Pubkey,
+    new_account: &Pubkey,
+    lamports: u64,
+    space: u64,
+    owner: &Pubkey,
+) -> Instruction {
+    let mut data = Vec::with_capacity(52);
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&lamports.to_le_bytes());
+    data.extend_from_slice(&space.to_le_bytes());
+    data.extend_from_slice(owner.as_ref());
+    Instruction {
+        program_id: program_id(SYSTEM_PROGRAM_ID),
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*new_account, true),
+        ],
+        data,
+    }
+}
+
+/// Token program `InitializeMint2` with `authority` as mint authority and
+/// no freeze authority.
+fn initialize_mint(mint: &Pubkey, authority: &Pubkey, decimals: u8) -> Instruction {
+    let mut data = vec![20, decimals];
+    data.extend_from_slice(authority.as_ref());
+    data.push(0);
+    Instruction {
+        program_id: program_id(TOKEN_PROGRAM_ID),
+        accounts: vec![AccountMeta::new(*mint, false)],
+        data,
+    }
+}
+
+/// Associated token program `CreateIdempotent`, so an existing account is
+/// not an error.
+fn create_associated_token_account(payer: &Pubkey, wallet: &Pubkey, mint: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: program_id(ASSOCIATED_TOKEN_PROGRAM_ID),
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(associated_token_address(wallet, mint), false),
+            AccountMeta::new_readonly(*wallet, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(program_id(SYSTEM_PROGRAM_ID), false),
+            AccountMeta::new_readonly(program_id(TOKEN_PROGRAM_ID), false),
+        ],
+        data: vec![1],
+    }
+}
+
+/// Token program `MintTo`.
+fn mint_to(mint: &Pubkey, destination: &Pubkey, authority: &Pubkey, amount: u64) -> Instruction {
+    let mut data = vec![7];
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id: program_id(TOKEN_PROGRAM_ID),
+        accounts: vec![
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data,
+    }
+}
+
+/// Create a new token mint with `payer` as its authority, open `recipient`'s
+/// associated token account for it and mint `amount` into it, all in one
+/// transaction.
+pub async fn mint_token(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    recipient: &Pubkey,
+    req: &TokenMintRequest,
+    cluster: &Cluster,
+    blockhashes: &BlockhashCache,
+) -> Result<TokenMintResponse, RpcError> {
+    let mint = Keypair::new();
+    let rent = rpc
+        .get_minimum_balance_for_rent_exemption(MINT_ACCOUNT_LEN as usize)
+        .await?;
+    let token_account = associated_token_address(recipient, &mint.pubkey());
+
+    let instructions = [
+        create_account(
+            &payer.pubkey(),
+            &mint.pubkey(),
+            rent,
+            MINT_ACCOUNT_LEN,
+            &program_id(TOKEN_PROGRAM_ID),
+        ),
+        initialize_mint(&mint.pubkey(), &payer.pubkey(), req.decimals),
+        create_associated_token_account(&payer.pubkey(), recipient, &mint.pubkey()),
+        mint_to(&mint.pubkey(), &token_account, &payer.pubkey(), req.amount),
+    ];
+    let blockhash = blockhashes.get(rpc).await?;
+    let mut tx = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    tx.sign(&[payer, &mint], blockhash.hash);
+
+    let sent = rpc
+        .send_and_confirm_transaction(&tx, Some(CommitmentLevel::Confirmed), None)
+        .await?;
+
+    Ok(TokenMintResponse {
+        explorer_url: cluster.tx_url(&sent.signature),
+        signature: sent.signature,
+        mint: mint.pubkey().to_string(),
+        token_account: token_account.to_string(),
+        recipient: recipient.to_string(),
+        decimals: req.decimals,
+        amount: req.amount,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_rpc::MockRpc;
+
+    #[tokio::test]
+    async fn test_mint_token_creates_mint_account_and_mints_to_recipient() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        let payer = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        let req = TokenMintRequest {
+            recipient: recipient.to_string(),
+            decimals: 6,
+            amount: 1_000_000,
+        };
+
+        let response = mint_token(
+            &rpc,
+            &payer,
+            &recipient,
+            &req,
+            &Cluster::Devnet,
+            &BlockhashCache::default(),
+        )
+        .await
+        .unwrap();
+
+        let tx = &mock.sent_transactions()[0];
+        let mint = Pubkey::from_str(&response.mint).unwrap();
+        assert_eq!(tx.signatures.len(), 2);
+        assert!(tx.verify().is_ok());
+        assert_eq!(
+            response.token_account,
+            associated_token_address(&recipient, &mint).to_string()
+        );
+
+        let programs: Vec<Pubkey> = tx
+            .message
+            .instructions
+            .iter()
+            .map(|ix| tx.message.account_keys[ix.program_id_index as usize])
+            .collect();
+        assert_eq!(
+            programs,
+            [
+                program_id(SYSTEM_PROGRAM_ID),
+                program_id(TOKEN_PROGRAM_ID),
+                program_id(ASSOCIATED_TOKEN_PROGRAM_ID),
+                program_id(TOKEN_PROGRAM_ID),
+            ]
+        );
+        let create = &tx.message.instructions[0].data;
+        assert_eq!(create[4..12], 1_461_600u64.to_le_bytes());
+        let mint_to = &tx.message.instructions[3].data;
+        assert_eq!(
+            mint_to[..],
+            [&[7u8][..], &1_000_000u64.to_le_bytes()].concat()
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Bytes of memo data per record, before chunking.
+pub const MEMO_SIZE_BYTES: &str = "memo_size_bytes";
+
+/// Ledger records whose on-chain copy no longer matches.
+pub const RECORD_VERIFY_MISMATCHES: &str = "record_verify_mismatches_total";
+
+/// Unix time of the most recent successful mint, for "no mints in N
+/// minutes" alerts.
+pub const LAST_MINT_UNIX_TS: &str = "last_mint_unix_ts";
+
+/// Mints submitted through `/mint`, `/mint/batch` or dead-letter replay,
+/// excluding dry runs and rejected batch duplicates.
+pub const MINTS_ATTEMPTED: &str = "mints_attempted_total";
+
+/// Attempted mints that succeeded.
+pub const MINTS_SUCCEEDED: &str = "mints_succeeded_total";
+
+/// Attempted mints that failed, labelled by failure `code`.
+pub const MINTS_FAILED: &str = "mints_failed_total";
+
+/// Seconds from submission until a transaction reached its target
+/// commitment.
+pub const CONFIRMATION_LATENCY_SECONDS: &str = "confirmation_latency_seconds";
+
+/// Payer balance as of the last `/health` check.
+pub const PAYER_BALANCE_SOL: &str = "payer_balance_sol";
+
+/// Buckets around the single-memo limit (566 bytes) and its multiples, so
+/// the histogram shows how close records run to needing another chunk.
+const MEMO_SIZE_BUCKETS: &[f64] = &[64.0, 128.0, 256.0, 384.0, 512.0, 566.0, 1132.0, 2264.0];
+
+/// Spans fast devnet confirmations up to the default 15 s confirm timeout.
+const CONFIRMATION_LATENCY_BUCKETS: &[f64] = &[0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 15.0, 30.0];
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Install the process-wide Prometheus recorder on first use and return the
+/// handle `/metrics` renders from. Metrics recorded before this is called
+/// are dropped.
+pub fn install() -> &'static PrometheusHandle {
+    HANDLE.get_or_init(|| {
+        PrometheusBuilder::new()
+            .set_buckets_for_metric(Matcher::Full(MEMO_SIZE_BYTES.into()), MEMO_SIZE_BUCKETS)
+            .expect("Invalid histogram buckets")
+            .set_buckets_for_metric(
+                Matcher::Full(CONFIRMATION_LATENCY_SECONDS.into()),
+                CONFIRMATION_LATENCY_BUCKETS,
+            )
+            .expect("Invalid histogram buckets")
+            .install_recorder()
+            .expect("Failed to install metrics recorder")
+    })
+}
+
+pub fn record_memo_size(bytes: usize) {
+    metrics::histogram!(MEMO_SIZE_BYTES).record(bytes as f64);
+}
+
+pub fn record_verify_mismatch() {
+    metrics::counter!(RECORD_VERIFY_MISMATCHES).increment(1);
+}
+
+pub fn record_mint(unix_ts: i64) {
+    metrics::gauge!(LAST_MINT_UNIX_TS).set(unix_ts as f64);
+}
+
+pub fn record_mint_attempt() {
+    metrics::counter!(MINTS_ATTEMPTED).increment(1);
+}
+
+pub fn record_mint_success() {
+    metrics::counter!(MINTS_SUCCEEDED).increment(1);
+}
+
+pub fn record_mint_failure(code: &'static str) {
+    metrics::counter!(MINTS_FAILED, "code" => code).increment(1);
+}
+
+pub fn record_confirmation_latency(elapsed: Duration) {
+    metrics::histogram!(CONFIRMATION_LATENCY_SECONDS).record(elapsed.as_secs_f64());
+}
+
+pub fn record_payer_balance(sol: f64) {
+    metrics::gauge!(PAYER_BALANCE_SOL).set(sol);
+}
@@ -0,0 +1,144 @@
+use crate::ledger::LedgerEntry;
+use crate::mint::{decode_memo, parse_chunk, reassemble_chunks, MintConfig};
+use crate::rpc::RpcClient;
+use crate::telemetry;
+
+/// Outcome of re-reading ledger entries from chain.
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    pub checked: usize,
+    /// Signatures whose on-chain record differs from the ledger.
+    pub mismatches: Vec<String>,
+    /// Signatures that could not be fetched or decoded.
+    pub errors: Vec<String>,
+}
+
+/// Memo strings of the memo-program instructions in a `jsonParsed`
+/// transaction.
+pub fn memos_in_transaction(tx: &serde_json::Value) -> Vec<String> {
+    tx["transaction"]["message"]["instructions"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|ix| ix["program"] == "spl-memo")
+        .filter_map(|ix| ix["parsed"].as_str().map(str::to_string))
+        .collect()
+}
+
+async fn fetch_memo(rpc: &RpcClient, signature: &str) -> Result<String, String> {
+    let tx = rpc
+        .get_transaction(signature)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Transaction {} not found", signature))?;
+    memos_in_transaction(&tx)
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Transaction {} has no memo", signature))
+}
+
+/// Read `entry`'s record back from chain, reassembling chunks, and report
+/// whether it matches what the ledger holds.
+pub async fn verify_entry(
+    rpc: &RpcClient,
+    entry: &LedgerEntry,
+    config: &MintConfig,
+) -> Result<bool, String> {
+    let first = fetch_memo(rpc, &entry.signature).await?;
+    let data = match parse_chunk(&first) {
+        Some((_, total, ..)) => {
+            let mut chunks = vec![first];
+            for signature in entry.signatures.iter().skip(1).take(total - 1) {
+                chunks.push(fetch_memo(rpc, signature).await?);
+            }
+            reassemble_chunks(&chunks)?
+        }
+        None => first,
+    };
+    let on_chain = decode_memo(&data, config)?;
+
+    let as_value = |record| serde_json::to_value(record).map_err(|e| e.to_string());
+    Ok(as_value(&on_chain)? == as_value(&entry.record)?)
+}
+
+/// Verify each of `entries`, logging and counting any mismatch.
+pub async fn verify_entries(
+    rpc: &RpcClient,
+    entries: &[LedgerEntry],
+    config: &MintConfig,
+) -> VerifyReport {
+    let mut report = VerifyReport::default();
+    for entry in entries {
+        report.checked += 1;
+        match verify_entry(rpc, entry, config).await {
+            Ok(true) => {}
+            Ok(false) => {
+                tracing::error!(
+                    "On-chain record for {} does not match the ledger (report_hash {})",
+                    entry.signature,
+                    entry.record.report_hash
+                );
+                telemetry::record_verify_mismatch();
+                report.mismatches.push(entry.signature.clone());
+            }
+            Err(e) => {
+                tracing::warn!("Could not re-verify {}: {}", entry.signature, e);
+                report.errors.push(entry.signature.clone());
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mint::{build_memo, encode_memo, MintRequest};
+    use crate::mock_rpc::MockRpc;
+    use serde_json::json;
+
+    fn parsed_tx(memo: &str) -> serde_json::Value {
+        json!({
+            "slot": 1,
+            "transaction": {"message": {"instructions": [
+                {"program": "spl-memo", "programId": "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr", "parsed": memo}
+            ]}}
+        })
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_ledger_entry_reported_as_mismatch() {
+        let mock = MockRpc::start().await;
+        let rpc = RpcClient::new(&mock.url);
+        let config = MintConfig::default();
+        let record = build_memo(
+            &MintRequest {
+                location_id: "iceland-reykjavik".into(),
+                ..Default::default()
+            },
+            &config,
+        );
+        let memo = encode_memo(&record, &config).unwrap();
+        mock.respond("getTransaction", parsed_tx(&memo));
+        mock.respond("getTransaction", parsed_tx(&memo));
+
+        let entry = |signature: &str, report_hash: &str| LedgerEntry {
+            signature: signature.into(),
+            signatures: vec![signature.into()],
+            record: crate::mint::MemoRecord {
+                report_hash: report_hash.into(),
+                ..record.clone()
+            },
+            minted_at: String::new(),
+        };
+        let entries = [
+            entry("intact", &record.report_hash),
+            entry("corrupted", "0000000000000000"),
+        ];
+
+        let report = verify_entries(&rpc, &entries, &config).await;
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.mismatches, vec!["corrupted".to_string()]);
+        assert!(report.errors.is_empty());
+    }
+}
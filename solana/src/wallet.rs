@@ -1,15 +1,178 @@
+use crate::keystore;
 use solana_keypair::Keypair;
 use std::fs;
+use std::io::Read;
 use std::path::Path;
 
-/// Load a Solana keypair from a JSON file (array of secret key bytes).
+/// Load a Solana keypair from a file holding either a JSON byte array (as
+/// written by `keygen`) or a base58 secret (as exported by `solana-keygen`
+/// and browser wallets). An encrypted wallet (`keygen --encrypt`) is
+/// unlocked with `WALLET_PASSWORD`, or a password prompt.
 pub fn load_wallet(path: &Path) -> Result<Keypair, String> {
-    let data = fs::read_to_string(path)
-        .map_err(|e| format!("Failed to read wallet file {}: {}", path.display(), e))?;
+    let data = read_wallet_file(path)?;
+    if keystore::is_encrypted(&data) {
+        let prompt = format!("Password for {}: ", path.display());
+        return load_encrypted_wallet(path, &keystore::password(&prompt)?);
+    }
+    parse_keypair(&data)
+}
+
+/// Load a wallet written by `keygen --encrypt`, unlocking it with
+/// `password`.
+pub fn load_encrypted_wallet(path: &Path, password: &str) -> Result<Keypair, String> {
+    decrypt_keypair(&read_wallet_file(path)?, password)
+}
+
+fn read_wallet_file(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read wallet file {}: {}", path.display(), e))
+}
+
+fn decrypt_keypair(data: &str, password: &str) -> Result<Keypair, String> {
+    let bytes = keystore::decrypt(data, password)?;
+    Keypair::try_from(bytes.as_slice()).map_err(|_| "Invalid keypair bytes".to_string())
+}
+
+/// Load a keypair from `reader`, e.g. stdin, in any format `load_wallet`
+/// accepts.
+pub fn load_from_reader(mut reader: impl Read) -> Result<Keypair, String> {
+    let mut data = String::new();
+    reader
+        .read_to_string(&mut data)
+        .map_err(|e| format!("Failed to read wallet: {}", e))?;
+    if keystore::is_encrypted(&data) {
+        return decrypt_keypair(&data, &keystore::password("Wallet password: ")?);
+    }
+    parse_keypair(&data)
+}
+
+/// Decode a JSON byte array or base58 secret. Errors never include the
+/// input.
+fn parse_keypair(data: &str) -> Result<Keypair, String> {
+    let data = data.trim();
+    let bytes = match serde_json::from_str::<Vec<u8>>(data) {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            let mut bytes = [0u8; 64];
+            five8::decode_64(data, &mut bytes).map_err(|_| {
+                "Wallet is neither a JSON byte array nor a base58 secret key".to_string()
+            })?;
+            bytes.to_vec()
+        }
+    };
+    Keypair::try_from(bytes.as_slice()).map_err(|_| "Invalid keypair bytes".to_string())
+}
+
+/// Describe why the wallet file's permissions are unsafe, if they are: like
+/// `ssh` with private keys, a secret key file should not be readable by group
+/// or other users. Always `None` on non-Unix platforms.
+pub fn permission_warning(path: &Path) -> Option<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = fs::metadata(path).ok()?.permissions().mode() & 0o777;
+        if mode & 0o077 != 0 {
+            return Some(format!(
+                "Wallet file {} has permissions {:o}; it should not be accessible by group or other users (try chmod 600)",
+                path.display(),
+                mode
+            ));
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_signer::Signer;
+
+    #[test]
+    fn test_load_from_reader_json_and_base58() {
+        let keypair = Keypair::new();
+        let json = serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap();
+        let base58 = format!("{}\n", keypair.to_base58_string());
+
+        for input in [json, base58] {
+            let loaded = load_from_reader(input.as_bytes()).unwrap();
+            assert_eq!(loaded.pubkey(), keypair.pubkey());
+        }
+    }
+
+    #[test]
+    fn test_load_wallet_accepts_json_and_base58_files() {
+        let keypair = Keypair::new();
+        let path = std::env::temp_dir().join(format!("wallet-formats-{}", std::process::id()));
+
+        fs::write(
+            &path,
+            serde_json::to_string(&keypair.to_bytes().to_vec()).unwrap(),
+        )
+        .unwrap();
+        let from_json = load_wallet(&path).unwrap();
+        fs::write(&path, keypair.to_base58_string()).unwrap();
+        let from_base58 = load_wallet(&path).unwrap();
+        fs::write(&path, "garbage!").unwrap();
+        let err = load_wallet(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(from_json.pubkey(), keypair.pubkey());
+        assert_eq!(from_base58.pubkey(), keypair.pubkey());
+        assert!(err.contains("JSON") && err.contains("base58"), "{}", err);
+    }
+
+    #[test]
+    fn test_load_from_reader_error_does_not_echo_secret() {
+        let err = load_from_reader("not-a-secret-0OIl".as_bytes()).unwrap_err();
+        assert!(!err.contains("not-a-secret"));
+    }
+
+    #[test]
+    fn test_encrypted_wallet_needs_right_password() {
+        let keypair = Keypair::new();
+        let path = std::env::temp_dir().join(format!("wallet-encrypted-{}", std::process::id()));
+        fs::write(
+            &path,
+            keystore::encrypt_with_cost(&keypair.to_bytes(), "correct horse", 4).unwrap(),
+        )
+        .unwrap();
+
+        let loaded = load_encrypted_wallet(&path, "correct horse");
+        let wrong = load_encrypted_wallet(&path, "battery staple");
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.unwrap().pubkey(), keypair.pubkey());
+        assert!(wrong.unwrap_err().contains("Wrong wallet password"));
+    }
+
+    #[cfg(unix)]
+    fn wallet_with_mode(name: &str, mode: u32) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}.json", name, std::process::id()));
+        fs::write(&path, "[]").unwrap();
+        use std::os::unix::fs::PermissionsExt;
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        path
+    }
 
-    let bytes: Vec<u8> = serde_json::from_str(&data)
-        .map_err(|e| format!("Invalid wallet JSON: {}", e))?;
+    #[cfg(unix)]
+    #[test]
+    fn test_world_readable_wallet_warns() {
+        let path = wallet_with_mode("wallet-0644", 0o644);
+        let warning = permission_warning(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(warning.unwrap().contains("644"));
+    }
 
-    Keypair::try_from(bytes.as_slice())
-        .map_err(|e| format!("Invalid keypair bytes: {}", e))
+    #[cfg(unix)]
+    #[test]
+    fn test_private_wallet_does_not_warn() {
+        let path = wallet_with_mode("wallet-0600", 0o600);
+        let warning = permission_warning(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(warning.is_none());
+    }
 }